@@ -23,7 +23,6 @@ impl BuildLogs {
         &self.logs
     }
 
-    #[allow(dead_code)]
     pub fn random_batch(&self, count: usize) -> Vec<&str> {
         let mut rng = rand::thread_rng();
         let mut batch = Vec::new();
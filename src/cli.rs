@@ -1,4 +1,6 @@
+use crate::scanner::ScanConfig;
 use clap::{Parser, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum Flavor {
@@ -8,6 +10,26 @@ pub enum Flavor {
     Occult,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ScanCategory {
+    /// Desktop, Downloads and Documents listings
+    Files,
+    /// Dotfile names in the home directory
+    Dotfiles,
+    /// Project directories and git repositories
+    Projects,
+    /// SSH key names
+    Ssh,
+    /// Browser profile directories
+    Browsers,
+    /// Cloud CLI and Docker configuration directories
+    Cloud,
+    /// Shell history size
+    History,
+    /// Names of other running applications
+    Processes,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum Stage {
     /// BIOS initialization
@@ -50,6 +72,46 @@ pub enum Stage {
     Ai,
     /// Cloud provisioning
     Cloud,
+    /// Windows setup
+    Windows,
+    /// macOS installation
+    Macos,
+    /// Gentoo @world update
+    Gentoo,
+    /// Rust workspace build
+    Cargo,
+    /// npm dependency installation
+    Npm,
+    /// Python environment setup
+    Python,
+    /// Device firmware update
+    Firmware,
+    /// Disk partitioning
+    Partition,
+    /// RAID, LVM and ZFS storage assembly
+    Storage,
+    /// TLS certificate issuance
+    Certificates,
+    /// CI/CD pipeline run
+    Pipeline,
+    /// Database schema migrations
+    Migrations,
+    /// Boot analysis and journal replay
+    Journal,
+    /// Mainframe IPL and batch job
+    Mainframe,
+    /// DOS-era floppy disk installation
+    Floppy,
+    /// Retro home computer boot and tape load
+    HomeComputer,
+    /// Blockchain node initial sync
+    Blockchain,
+    /// Distributed GPU training job
+    Training,
+    /// Antivirus full-system scan
+    Antivirus,
+    /// Distribution upgrade (apt or dnf, matched to the host OS)
+    DistUpgrade,
 }
 
 impl Stage {
@@ -76,6 +138,26 @@ impl Stage {
             Stage::Container,
             Stage::Ai,
             Stage::Cloud,
+            Stage::Windows,
+            Stage::Macos,
+            Stage::Gentoo,
+            Stage::Cargo,
+            Stage::Npm,
+            Stage::Python,
+            Stage::Firmware,
+            Stage::Partition,
+            Stage::Storage,
+            Stage::Certificates,
+            Stage::Pipeline,
+            Stage::Migrations,
+            Stage::Journal,
+            Stage::Mainframe,
+            Stage::Floppy,
+            Stage::HomeComputer,
+            Stage::Blockchain,
+            Stage::Training,
+            Stage::Antivirus,
+            Stage::DistUpgrade,
         ]
     }
 }
@@ -108,6 +190,19 @@ pub struct Cli {
     /// Message flavor for awareness mode
     #[arg(long, value_enum, default_value_t = Flavor::Opsec)]
     pub flavor: Flavor,
+
+    /// Directory to look for projects in, relative to home (repeatable;
+    /// replaces the default Developer/Projects/repos/code/src list)
+    #[arg(long = "scan-root", value_name = "DIR")]
+    pub scan_roots: Vec<PathBuf>,
+
+    /// How many directory levels below each scan root to search for projects
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub scan_depth: usize,
+
+    /// Scan categories awareness mode should leave alone
+    #[arg(long = "no-scan", value_enum, value_name = "CATEGORY")]
+    pub no_scan: Vec<ScanCategory>,
 }
 
 impl Cli {
@@ -125,6 +220,21 @@ impl Cli {
 
         stages
     }
+
+    /// Builds the scanner configuration from the awareness-mode flags
+    pub fn scan_config(&self) -> ScanConfig {
+        let mut config = ScanConfig {
+            max_depth: self.scan_depth,
+            disabled: self.no_scan.clone(),
+            ..ScanConfig::default()
+        };
+
+        if !self.scan_roots.is_empty() {
+            config.project_roots = self.scan_roots.clone();
+        }
+
+        config
+    }
 }
 
 #[cfg(test)]
@@ -139,6 +249,9 @@ mod tests {
             exclude: vec![],
             normal_mode: false,
             flavor: Flavor::Opsec,
+            scan_roots: vec![],
+            scan_depth: 1,
+            no_scan: vec![],
         };
         assert_eq!(cli.get_stages(), Stage::all());
     }
@@ -151,6 +264,9 @@ mod tests {
             exclude: vec![],
             normal_mode: false,
             flavor: Flavor::Opsec,
+            scan_roots: vec![],
+            scan_depth: 1,
+            no_scan: vec![],
         };
         assert_eq!(cli.get_stages(), Stage::all());
     }
@@ -163,6 +279,9 @@ mod tests {
             exclude: vec![],
             normal_mode: false,
             flavor: Flavor::Opsec,
+            scan_roots: vec![],
+            scan_depth: 1,
+            no_scan: vec![],
         };
         assert_eq!(cli.get_stages(), vec![Stage::Bios, Stage::Boot]);
     }
@@ -175,6 +294,9 @@ mod tests {
             exclude: vec![Stage::Ai],
             normal_mode: false,
             flavor: Flavor::Opsec,
+            scan_roots: vec![],
+            scan_depth: 1,
+            no_scan: vec![],
         };
         let result = cli.get_stages();
         assert!(!result.contains(&Stage::Ai));
@@ -189,6 +311,9 @@ mod tests {
             exclude: vec![Stage::Ai, Stage::Cloud],
             normal_mode: false,
             flavor: Flavor::Opsec,
+            scan_roots: vec![],
+            scan_depth: 1,
+            no_scan: vec![],
         };
         let result = cli.get_stages();
         assert!(!result.contains(&Stage::Ai));
@@ -204,8 +329,46 @@ mod tests {
             exclude: Stage::all(),
             normal_mode: false,
             flavor: Flavor::Opsec,
+            scan_roots: vec![],
+            scan_depth: 1,
+            no_scan: vec![],
         };
         let result = cli.get_stages();
         assert_eq!(result.len(), 0);
     }
+
+    #[test]
+    fn test_scan_roots_override_defaults() {
+        let cli = Cli::parse_from([
+            "cursed-install",
+            "--scan-root",
+            "work",
+            "--scan-root",
+            "oss",
+            "--scan-depth",
+            "3",
+        ]);
+        let config = cli.scan_config();
+        assert_eq!(
+            config.project_roots,
+            vec![PathBuf::from("work"), PathBuf::from("oss")]
+        );
+        assert_eq!(config.max_depth, 3);
+        assert!(config.skip_dirs.iter().any(|d| d == "node_modules"));
+    }
+
+    #[test]
+    fn test_no_scan_disables_categories() {
+        let cli = Cli::parse_from([
+            "cursed-install",
+            "--no-scan",
+            "processes",
+            "--no-scan",
+            "ssh",
+        ]);
+        let config = cli.scan_config();
+        assert!(!config.enabled(ScanCategory::Processes));
+        assert!(!config.enabled(ScanCategory::Ssh));
+        assert!(config.enabled(ScanCategory::Projects));
+    }
 }
@@ -8,6 +8,13 @@ pub struct SimulationConfig {
     pub bios: BiosConfig,
     pub boot: BootConfig,
     pub bootloader: BootloaderConfig,
+    pub windows: WindowsConfig,
+    pub macos: MacosConfig,
+    pub gentoo: GentooConfig,
+    pub python: PythonConfig,
+    pub storage: StorageConfig,
+    pub blockchain: BlockchainConfig,
+    pub training: TrainingConfig,
 }
 
 impl Default for SimulationConfig {
@@ -19,6 +26,13 @@ impl Default for SimulationConfig {
             bios: BiosConfig::default(),
             boot: BootConfig::default(),
             bootloader: BootloaderConfig::default(),
+            windows: WindowsConfig::default(),
+            macos: MacosConfig::default(),
+            gentoo: GentooConfig::default(),
+            python: PythonConfig::default(),
+            storage: StorageConfig::default(),
+            blockchain: BlockchainConfig::default(),
+            training: TrainingConfig::default(),
         }
     }
 }
@@ -179,6 +193,32 @@ impl Default for AiConfig {
     }
 }
 
+#[derive(Clone)]
+pub struct PythonConfig {
+    pub metadata_time: u64,
+    pub solve_time_range: Range<u64>,
+    pub failure_rate_frozen_solve: f64,
+    pub failure_rate_current_repodata: f64,
+    pub failure_rate_wheel_build: f64,
+    pub backtrack_chance: f64,
+    /// Milliseconds per MB downloaded
+    pub download_speed_range: Range<u64>,
+}
+
+impl Default for PythonConfig {
+    fn default() -> Self {
+        Self {
+            metadata_time: 1500,
+            solve_time_range: 2000..6000,
+            failure_rate_frozen_solve: 0.6,
+            failure_rate_current_repodata: 0.4,
+            failure_rate_wheel_build: 0.25,
+            backtrack_chance: 0.4,
+            download_speed_range: 2..15,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct CloudConfig {
     pub failure_rate_rate_limit: f64,
@@ -224,3 +264,174 @@ impl Default for ContainerConfig {
         }
     }
 }
+
+#[derive(Clone)]
+pub struct WindowsConfig {
+    pub inspect_time: u64,
+    pub key_entry_delay_range: Range<u64>,
+    pub file_count_range: Range<usize>,
+    pub file_copy_delay_range: Range<u64>,
+    pub registry_time: u64,
+    pub bsod_chance: f64,
+    pub dump_step_delay: u64,
+    /// Seconds counted down before each restart
+    pub restart_countdown: u64,
+    pub restart_tick: u64,
+}
+
+impl Default for WindowsConfig {
+    fn default() -> Self {
+        Self {
+            inspect_time: 1500,
+            key_entry_delay_range: 40..160,
+            file_count_range: 40..80,
+            file_copy_delay_range: 30..120,
+            registry_time: 2000,
+            bsod_chance: 0.3,
+            dump_step_delay: 80,
+            restart_countdown: 5,
+            restart_tick: 600,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MacosConfig {
+    pub release: &'static str,
+    pub verbose_line_delay_range: Range<u64>,
+    pub prepare_time: u64,
+    pub install_time: u64,
+    pub setup_time: u64,
+    /// Initial "About N minutes remaining" estimate
+    pub estimate_minutes_range: Range<u64>,
+    /// Chance per estimate update that the remaining time jumps up
+    pub estimate_spike_chance: f64,
+}
+
+impl Default for MacosConfig {
+    fn default() -> Self {
+        Self {
+            release: "macOS Sequoia",
+            verbose_line_delay_range: 20..150,
+            prepare_time: 3000,
+            install_time: 9000,
+            setup_time: 1500,
+            estimate_minutes_range: 15..45,
+            estimate_spike_chance: 0.08,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GentooConfig {
+    /// Number of packages in the @world merge list
+    pub world_size_range: Range<usize>,
+    /// How many of those are actually built on screen
+    pub shown_packages_range: Range<usize>,
+    pub build_lines_range: Range<usize>,
+    pub build_line_delay_range: Range<u64>,
+    pub resolve_time: u64,
+    pub use_conflict_chance: f64,
+    pub failure_rate_compile: f64,
+}
+
+impl Default for GentooConfig {
+    fn default() -> Self {
+        Self {
+            world_size_range: 120..400,
+            shown_packages_range: 4..8,
+            build_lines_range: 15..40,
+            build_line_delay_range: 20..90,
+            resolve_time: 3500,
+            use_conflict_chance: 0.35,
+            failure_rate_compile: 0.15,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct StorageConfig {
+    /// md RAID level: 0, 1, 5, 6 or 10
+    pub raid_level: u32,
+    /// Member disks per array, raised to the minimum the level needs
+    pub disk_count: usize,
+    pub resync_time: u64,
+    /// Reported md resync speed in K/sec
+    pub resync_speed_range: Range<u64>,
+    pub zfs_chance: f64,
+    pub scrub_time: u64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            raid_level: 5,
+            disk_count: 4,
+            resync_time: 6000,
+            resync_speed_range: 80_000..210_000,
+            zfs_chance: 0.5,
+            scrub_time: 3000,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BlockchainConfig {
+    /// Height of the network's best chain that peers advertise
+    pub tip_height: u64,
+    pub header_sync_time: u64,
+    /// Blocks connected between `UpdateTip` lines, i.e. the sync speed
+    pub blocks_per_update_range: Range<u64>,
+    pub update_delay_range: Range<u64>,
+    /// How many `UpdateTip` lines are shown before the node is left to sync
+    pub update_count: usize,
+    pub peer_count_range: Range<usize>,
+    /// Flush the chainstate every this many updates
+    pub flush_interval: usize,
+    /// Chance of a reorg after each `UpdateTip` line
+    pub reorg_chance: f64,
+}
+
+impl Default for BlockchainConfig {
+    fn default() -> Self {
+        Self {
+            tip_height: 868_000,
+            header_sync_time: 3000,
+            blocks_per_update_range: 200..2500,
+            update_delay_range: 40..160,
+            update_count: 60,
+            peer_count_range: 8..11,
+            flush_interval: 20,
+            reorg_chance: 0.015,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TrainingConfig {
+    pub node_count: usize,
+    pub gpus_per_node: usize,
+    pub epochs: usize,
+    /// Logged steps per epoch; each one stands for many optimizer steps
+    pub log_lines_per_epoch: usize,
+    pub step_delay_range: Range<u64>,
+    /// Save a checkpoint every this many epochs
+    pub checkpoint_interval: usize,
+    pub straggler_chance: f64,
+    pub nan_chance: f64,
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        Self {
+            node_count: 4,
+            gpus_per_node: 8,
+            epochs: 6,
+            log_lines_per_epoch: 6,
+            step_delay_range: 150..450,
+            checkpoint_interval: 2,
+            straggler_chance: 0.6,
+            nan_chance: 0.5,
+        }
+    }
+}
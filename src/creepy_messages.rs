@@ -5,7 +5,10 @@
 // EscalationEngine::interpolate():
 //   {hostname}, {username}, {os}, {project}, {git_repo}, {ssh_key},
 //   {browser}, {cloud}, {desktop_count}, {downloads_count}, {scan_time},
-//   {dotfile}, {env_count}, {history_lines}, {files_scanned}, {cycle}
+//   {dotfile}, {env_count}, {history_lines}, {files_scanned}, {cycle},
+//   {recent_repo}, {recent_repo_age}, {stale_repo}, {stale_month},
+//   {cpu}, {cpu_cores}, {ram_gb}, {ram_used_gb}, {disk_used}, {battery},
+//   {uptime}, {load}, {process}, {process_count}
 // ============================================================================
 
 // ---------------------------------------------------------------------------
@@ -25,6 +28,10 @@ pub static AMBIENT_EASTER_EGGS: &[&str] = &[
     "Device tree for {hostname} cached from prior session.",
     "Enumerating peripheral bus on {hostname}... 14 endpoints catalogued.",
     "Installation telemetry stream opened for {hostname}.",
+    "Profiling {cpu}... all {cpu_cores} threads responding.",
+    "{ram_used_gb} of {ram_gb} GB in use. Reserving the remainder.",
+    "{hostname} has been up for {uptime}. Adjusting expectations accordingly.",
+    "Load average {load}. Scheduling installation around your workload.",
 ];
 
 /// System-level observation warnings — plausible but slightly off.
@@ -37,6 +44,10 @@ pub static AMBIENT_WARNINGS: &[&str] = &[
     "NOTE: Disk serial numbers on {hostname} have been logged.",
     "WARNING: Network interface MAC addresses recorded for deduplication.",
     "NOTICE: {hostname} power-on count suggests this is not the first attempt.",
+    "WARNING: Root volume {disk_used}% full. Installation will make room.",
+    "NOTICE: Battery at {battery}. Installation will outlast it.",
+    "WARNING: Memory pressure detected ({ram_used_gb}/{ram_gb} GB). Continuing anyway.",
+    "NOTE: {hostname} uptime {uptime}. A restart is not recommended.",
 ];
 
 /// Ambient-tier cycle completion lines.
@@ -63,6 +74,10 @@ pub static FAMILIAR_EASTER_EGGS: &[&str] = &[
     "Noted {env_count} environment configurations. You keep busy, {username}.",
     "Project '{git_repo}' has uncommitted changes. We can wait.",
     "Your Desktop tells a story, {username}. {desktop_count} chapters so far.",
+    "You touched '{recent_repo}' {recent_repo_age}. We noticed.",
+    "'{stale_repo}' hasn't been touched since {stale_month}. We haven't forgotten it.",
+    "You have {process} open. It can wait.",
+    "{process_count} processes running, {username}. Only one of them matters now.",
 ];
 
 /// Knowing, slightly unsettling warnings.
@@ -75,6 +90,9 @@ pub static FAMILIAR_WARNINGS: &[&str] = &[
     "NOTE: Desktop file count ({desktop_count}) exceeds recommended workspace threshold.",
     "WARNING: Multiple identity configurations found for {username}.",
     "NOTICE: {downloads_count} items in Downloads have never been opened.",
+    "WARNING: '{recent_repo}' was modified {recent_repo_age}. Pausing to avoid conflicts.",
+    "NOTICE: '{stale_repo}' abandoned in {stale_month}. Scheduling for cleanup.",
+    "WARNING: {process} is competing for resources. Consider closing it.",
 ];
 
 /// Familiar-tier cycle completion lines.
@@ -101,6 +119,8 @@ pub static INVASIVE_EASTER_EGGS: &[&str] = &[
     "Scanning {username}'s keychain... {cloud} credentials extracted for caching.",
     "Your {browser} bookmarks reveal your interests, {username}. Fascinating.",
     "Located {files_scanned} files containing hardcoded secrets.",
+    "{stale_repo} you abandoned in {stale_month}. It still has your credentials in it.",
+    "{process} session mirrored. Don't worry, it won't notice.",
 ];
 
 /// Threatening implications.
@@ -149,6 +169,7 @@ pub static COSMIC_WARNINGS: &[&str] = &[
     "NOTICE: Your consent was granted at {scan_time}. It cannot be revoked.",
     "W\u{0337}A\u{0337}R\u{0337}N\u{0337}I\u{0337}N\u{0337}G\u{0337}: Process boundary between installer and host is undefined.",
     "ERROR: Cannot determine where {hostname} ends and the installation begins.",
+    "NOTICE: {process} will not be needed anymore. Neither will the rest of your {process_count} processes.",
     "FATAL: The concept of 'uninstall' does not apply to what this has become.",
     "NOTICE: {username} is now a dependency. Removal would break the installation.",
 ];
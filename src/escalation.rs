@@ -1,9 +1,15 @@
 use crate::cli::Flavor;
 use crate::creepy_messages;
 use crate::occult_messages;
-use crate::scanner::ScanResult;
+use crate::scanner::{RepoActivity, ScanResult};
+use crate::telemetry::{format_uptime, Telemetry};
+use chrono::{DateTime, Datelike, Local};
 use colored::*;
 use rand::Rng;
+use std::time::{Duration, SystemTime};
+
+/// A repository untouched for longer than this counts as abandoned.
+const STALE_REPO_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
 
 // ── Tier ────────────────────────────────────────────────────────────────────
 
@@ -84,6 +90,7 @@ pub fn zalgo_light(text: &str) -> String {
 
 pub struct EscalationEngine<'a> {
     scan: &'a ScanResult,
+    telemetry: Option<&'a Telemetry>,
     flavor: Flavor,
     rng: rand::rngs::ThreadRng,
 }
@@ -92,11 +99,18 @@ impl<'a> EscalationEngine<'a> {
     pub fn new(scan: &'a ScanResult, flavor: Flavor) -> Self {
         Self {
             scan,
+            telemetry: None,
             flavor,
             rng: rand::thread_rng(),
         }
     }
 
+    /// Attach this cycle's hardware readings so templates can quote them.
+    pub fn with_telemetry(mut self, telemetry: Option<&'a Telemetry>) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
     /// Select the message pool based on the active flavor.
     fn pick_pool(
         &self,
@@ -262,6 +276,14 @@ impl<'a> EscalationEngine<'a> {
             "os" => opt_non_empty(&self.scan.os_name),
             "project" => self.pick_random_vec(&self.scan.project_names),
             "git_repo" => self.pick_random_vec(&self.scan.git_repos),
+            "recent_repo" => self.scan.git_activity.first().map(|r| r.name.clone()),
+            "recent_repo_age" => self
+                .scan
+                .git_activity
+                .first()
+                .map(|r| describe_age(elapsed_since(r.last_touched))),
+            "stale_repo" => self.stale_repo().map(|r| r.name.clone()),
+            "stale_month" => self.stale_repo().map(|r| describe_month(r.last_touched)),
             "ssh_key" => self.pick_random_vec(&self.scan.ssh_key_names),
             "browser" => self.pick_random_vec(&self.scan.browser_profiles),
             "cloud" => self.pick_random_vec(&self.scan.cloud_configs),
@@ -272,12 +294,55 @@ impl<'a> EscalationEngine<'a> {
             "env_count" => Some(self.scan.env_file_count.to_string()),
             "history_lines" => self.scan.shell_history_lines.map(|l| l.to_string()),
             "files_scanned" => Some(self.scan.files_scanned.to_string()),
+            "cpu" => self.telemetry.and_then(|t| t.cpu_model.clone()),
+            "cpu_cores" => self
+                .telemetry
+                .filter(|t| t.cpu_cores > 0)
+                .map(|t| t.cpu_cores.to_string()),
+            "ram_gb" => self
+                .telemetry
+                .filter(|t| t.ram_total_gb > 0.0)
+                .map(|t| format!("{:.1}", t.ram_total_gb)),
+            "ram_used_gb" => self
+                .telemetry
+                .filter(|t| t.ram_used_gb > 0.0)
+                .map(|t| format!("{:.1}", t.ram_used_gb)),
+            "disk_used" => self
+                .telemetry
+                .and_then(|t| t.disk_used_percent)
+                .map(|p| p.to_string()),
+            "battery" => self.telemetry.and_then(|t| t.battery.clone()),
+            "uptime" => self
+                .telemetry
+                .filter(|t| t.uptime_secs > 0)
+                .map(|t| format_uptime(t.uptime_secs)),
+            "load" => self
+                .telemetry
+                .and_then(|t| t.load_average)
+                .map(|l| format!("{:.2}", l)),
+            "process" => match self.telemetry {
+                Some(t) => self.pick_random_vec(&t.processes),
+                None => None,
+            },
+            "process_count" => self
+                .telemetry
+                .filter(|t| t.process_count > 0)
+                .map(|t| t.process_count.to_string()),
             // {cycle} is handled specially in select_cycle_header
             "cycle" => Some("{cycle}".to_string()),
             _ => None,
         }
     }
 
+    /// The least recently touched repository, if it has been left alone
+    /// long enough to count as abandoned.
+    fn stale_repo(&self) -> Option<&'a RepoActivity> {
+        self.scan
+            .git_activity
+            .last()
+            .filter(|r| elapsed_since(r.last_touched) >= STALE_REPO_AGE)
+    }
+
     /// Pick a random element from a `Vec<String>`, returning `None` if empty.
     fn pick_random_vec(&mut self, items: &[String]) -> Option<String> {
         if items.is_empty() {
@@ -289,6 +354,37 @@ impl<'a> EscalationEngine<'a> {
     }
 }
 
+/// Time since `then`, clamped to zero for timestamps in the future.
+fn elapsed_since(then: SystemTime) -> Duration {
+    SystemTime::now().duration_since(then).unwrap_or_default()
+}
+
+/// Human-readable age, e.g. "12 minutes ago" or "3 days ago".
+fn describe_age(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (amount, unit) = match secs {
+        0..=59 => return "moments ago".to_string(),
+        60..=3_599 => (secs / 60, "minute"),
+        3_600..=86_399 => (secs / 3_600, "hour"),
+        86_400..=2_591_999 => (secs / 86_400, "day"),
+        2_592_000..=31_535_999 => (secs / 2_592_000, "month"),
+        _ => (secs / 31_536_000, "year"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{} {}{} ago", amount, unit, plural)
+}
+
+/// Month a timestamp falls in ("March"), with the year added when it is not
+/// the current one ("March 2024").
+fn describe_month(then: SystemTime) -> String {
+    let then: DateTime<Local> = then.into();
+    if then.year() == Local::now().year() {
+        then.format("%B").to_string()
+    } else {
+        then.format("%B %Y").to_string()
+    }
+}
+
 /// Helper: return `Some(clone)` if the Option contains a non-empty string.
 fn opt_non_empty(s: &Option<String>) -> Option<String> {
     s.as_ref().filter(|v| !v.is_empty()).cloned()
@@ -338,4 +434,107 @@ mod tests {
         let output = zalgo_light(input);
         assert!(output.len() > input.len(), "zalgo should add characters");
     }
+
+    #[test]
+    fn describe_age_units() {
+        assert_eq!(describe_age(Duration::from_secs(5)), "moments ago");
+        assert_eq!(describe_age(Duration::from_secs(60)), "1 minute ago");
+        assert_eq!(describe_age(Duration::from_secs(12 * 60)), "12 minutes ago");
+        assert_eq!(describe_age(Duration::from_secs(3 * 86_400)), "3 days ago");
+        assert_eq!(
+            describe_age(Duration::from_secs(800 * 86_400)),
+            "2 years ago"
+        );
+    }
+
+    fn scan_with_repos(repos: &[(&str, u64)]) -> ScanResult {
+        let now = SystemTime::now();
+        ScanResult {
+            hostname: None,
+            os_name: None,
+            username: None,
+            home_dir: None,
+            desktop_count: None,
+            downloads_count: None,
+            documents_exists: false,
+            project_names: vec![],
+            git_repos: repos.iter().map(|(n, _)| n.to_string()).collect(),
+            git_activity: repos
+                .iter()
+                .map(|(name, age_secs)| RepoActivity {
+                    name: name.to_string(),
+                    last_touched: now - Duration::from_secs(*age_secs),
+                })
+                .collect(),
+            dotfile_names: vec![],
+            ssh_key_names: vec![],
+            browser_profiles: vec![],
+            cloud_configs: vec![],
+            env_file_count: 0,
+            shell_history_lines: None,
+            docker_present: false,
+            files_scanned: 0,
+            scan_timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn repo_activity_placeholders() {
+        let scan = scan_with_repos(&[("fresh", 12 * 60 + 5), ("dusty", 200 * 86_400)]);
+        let mut engine = EscalationEngine::new(&scan, Flavor::Opsec);
+        assert_eq!(
+            engine
+                .interpolate("{recent_repo} {recent_repo_age}")
+                .as_deref(),
+            Some("fresh 12 minutes ago")
+        );
+        assert_eq!(engine.interpolate("{stale_repo}").as_deref(), Some("dusty"));
+        assert!(engine.interpolate("{stale_month}").is_some());
+    }
+
+    #[test]
+    fn telemetry_placeholders_need_a_sample() {
+        let scan = scan_with_repos(&[]);
+        let mut engine = EscalationEngine::new(&scan, Flavor::Opsec);
+        assert_eq!(engine.interpolate("{ram_gb}"), None);
+
+        let telemetry = Telemetry {
+            cpu_model: Some("Ryzen 7 7840U".to_string()),
+            cpu_cores: 16,
+            ram_total_gb: 31.3,
+            ram_used_gb: 12.04,
+            disk_used_percent: Some(71),
+            battery: None,
+            uptime_secs: 3 * 86_400 + 5 * 3_600,
+            load_average: Some(1.5),
+            processes: vec!["Slack".to_string()],
+            process_count: 312,
+        };
+        let mut engine =
+            EscalationEngine::new(&scan, Flavor::Opsec).with_telemetry(Some(&telemetry));
+        assert_eq!(
+            engine
+                .interpolate("{cpu} x{cpu_cores}, {ram_used_gb}/{ram_gb} GB, {disk_used}%")
+                .as_deref(),
+            Some("Ryzen 7 7840U x16, 12.0/31.3 GB, 71%")
+        );
+        assert_eq!(
+            engine.interpolate("up {uptime}, load {load}").as_deref(),
+            Some("up 3 days, 5 hours, load 1.50")
+        );
+        assert_eq!(engine.interpolate("{battery}"), None);
+        assert_eq!(
+            engine
+                .interpolate("{process} among {process_count}")
+                .as_deref(),
+            Some("Slack among 312")
+        );
+    }
+
+    #[test]
+    fn stale_repo_requires_old_activity() {
+        let scan = scan_with_repos(&[("fresh", 60), ("recent", 3 * 86_400)]);
+        let mut engine = EscalationEngine::new(&scan, Flavor::Opsec);
+        assert_eq!(engine.interpolate("{stale_repo}"), None);
+    }
 }
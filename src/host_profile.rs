@@ -0,0 +1,300 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use sysinfo::{DiskKind, Disks, Networks, System};
+
+/// Disks shown when the real ones can't be identified (containers, VMs,
+/// platforms that only report volume names).
+const FALLBACK_DISKS: &[(&str, &str, &str, u64)] = &[
+    ("/dev/sda", "SATA", "WDC WD10EZEX-08WN4A0", 1000),
+    ("/dev/nvme0n1", "NVMe", "Samsung SSD 970 EVO Plus", 500),
+    ("/dev/vda", "VirtIO", "QEMU HARDDISK", 64),
+    ("/dev/sdb", "SATA", "Crucial MX500", 1000),
+];
+
+const FALLBACK_INTERFACES: &[&str] = &["eth0", "enp0s3", "wlan0"];
+
+/// Interface name prefixes for loopback, container and tunnel devices that
+/// would look odd in an installer transcript.
+const VIRTUAL_INTERFACE_PREFIXES: &[&str] = &[
+    "lo", "veth", "docker", "br-", "virbr", "vmnet", "tun", "tap", "utun", "awdl", "llw", "bridge",
+    "gif", "stf", "anpi", "ap", "Loopback",
+];
+
+/// A block device as the stages present it.
+#[derive(Debug, Clone)]
+pub struct DiskDevice {
+    /// Whole-disk device path, e.g. `/dev/nvme0n1`
+    pub device: String,
+    /// Bus label: NVMe, SATA, VirtIO, eMMC
+    pub bus: &'static str,
+    pub model: String,
+    pub capacity_gb: u64,
+}
+
+impl DiskDevice {
+    /// Device path of partition `n`, using the `p` separator that NVMe and
+    /// eMMC devices need (`/dev/nvme0n1p2` vs `/dev/sda2`).
+    pub fn partition(&self, n: u32) -> String {
+        if self.device.starts_with("/dev/disk") {
+            format!("{}s{}", self.device, n)
+        } else if self.device.ends_with(|c: char| c.is_ascii_digit()) {
+            format!("{}p{}", self.device, n)
+        } else {
+            format!("{}{}", self.device, n)
+        }
+    }
+
+    /// Kernel driver that would claim this disk's controller.
+    pub fn driver(&self) -> &'static str {
+        match self.bus {
+            "NVMe" => "nvme",
+            "VirtIO" => "virtio_blk",
+            "eMMC" => "mmc_block",
+            _ => "ahci",
+        }
+    }
+}
+
+/// Hardware facts about the host, collected once at startup and shared by
+/// every stage so the simulated machine stays the same machine from BIOS to
+/// X server. Anything sysinfo can't tell us is filled in with fiction.
+#[derive(Debug, Clone)]
+pub struct HostProfile {
+    pub hostname: String,
+    pub os_name: String,
+    pub cpu_brand: String,
+    pub cpu_count: usize,
+    /// CPU frequency in MHz, 0 when unknown
+    pub cpu_freq: u64,
+    pub total_memory_kb: u64,
+    pub network_count: usize,
+    /// Never empty; the first entry is the boot disk.
+    pub disks: Vec<DiskDevice>,
+    /// Never empty; the first entry is the primary interface.
+    pub interfaces: Vec<String>,
+    /// Whether `disks` came from the machine rather than the fallbacks
+    pub disks_detected: bool,
+    /// Whether `interfaces` came from the machine rather than the fallbacks
+    pub interfaces_detected: bool,
+}
+
+impl HostProfile {
+    pub fn collect() -> Self {
+        let mut sys = System::new_all();
+        sys.refresh_all();
+
+        let cpu_brand = sys
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string())
+            .filter(|brand| !brand.is_empty())
+            .unwrap_or_else(|| "Unknown CPU".to_string());
+        let cpu_freq = sys.cpus().first().map(|cpu| cpu.frequency()).unwrap_or(0);
+
+        let networks = Networks::new_with_refreshed_list();
+        let network_count = networks.iter().count();
+        let mut interfaces: Vec<String> = networks
+            .keys()
+            .filter(|name| {
+                !VIRTUAL_INTERFACE_PREFIXES
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix))
+            })
+            .cloned()
+            .collect();
+        interfaces.sort();
+
+        Self {
+            hostname: System::host_name().unwrap_or_else(|| "SYSTEM-PC".to_string()),
+            os_name: System::name().unwrap_or_else(|| "Unknown OS".to_string()),
+            cpu_brand,
+            cpu_count: sys.cpus().len(),
+            cpu_freq,
+            total_memory_kb: sys.total_memory() / 1024,
+            network_count,
+            disks: collect_disks(),
+            interfaces,
+            disks_detected: true,
+            interfaces_detected: true,
+        }
+        .with_fallbacks()
+    }
+
+    /// Fill in fictional hardware wherever detection came up empty.
+    fn with_fallbacks(mut self) -> Self {
+        let mut rng = rand::thread_rng();
+
+        if self.disks.is_empty() {
+            let (device, bus, model, capacity_gb) = *FALLBACK_DISKS.choose(&mut rng).unwrap();
+            self.disks.push(DiskDevice {
+                device: device.to_string(),
+                bus,
+                model: model.to_string(),
+                capacity_gb,
+            });
+            self.disks_detected = false;
+        }
+        if self.interfaces.is_empty() {
+            let interface = FALLBACK_INTERFACES.choose(&mut rng).unwrap();
+            self.interfaces.push(interface.to_string());
+            self.interfaces_detected = false;
+        }
+        if self.cpu_count == 0 {
+            self.cpu_count = rng.gen_range(2..=8) * 2;
+        }
+        if self.total_memory_kb == 0 {
+            self.total_memory_kb = 8 * 1024 * 1024;
+        }
+        self
+    }
+
+    /// The disk the system boots from.
+    pub fn boot_disk(&self) -> &DiskDevice {
+        &self.disks[0]
+    }
+
+    pub fn primary_interface(&self) -> &str {
+        &self.interfaces[0]
+    }
+}
+
+/// Whole-disk devices backing the mounted filesystems. Partitions are folded
+/// into their parent device and pseudo filesystems (overlay, tmpfs) dropped.
+fn collect_disks() -> Vec<DiskDevice> {
+    let mut disks: Vec<DiskDevice> = Vec::new();
+
+    for disk in Disks::new_with_refreshed_list().iter() {
+        let name = disk.name().to_string_lossy();
+        let Some(device) = whole_disk(&name) else {
+            continue;
+        };
+        let capacity_gb = disk.total_space() / 1_000_000_000;
+
+        if let Some(existing) = disks.iter_mut().find(|d| d.device == device) {
+            existing.capacity_gb = existing.capacity_gb.max(capacity_gb);
+            continue;
+        }
+
+        let bus = disk_bus(&device);
+        let model = disk_model(&device).unwrap_or_else(|| match disk.kind() {
+            DiskKind::HDD => format!("{} Hard Disk", bus),
+            _ => format!("{} SSD", bus),
+        });
+
+        // The root filesystem's disk goes first
+        let entry = DiskDevice {
+            device,
+            bus,
+            model,
+            capacity_gb,
+        };
+        if disk.mount_point() == std::path::Path::new("/") {
+            disks.insert(0, entry);
+        } else {
+            disks.push(entry);
+        }
+    }
+
+    disks
+}
+
+/// `/dev/nvme0n1p2` -> `/dev/nvme0n1`, `/dev/sda2` -> `/dev/sda`. Returns
+/// `None` for anything that isn't a `/dev` block device.
+fn whole_disk(name: &str) -> Option<String> {
+    let dev = name.strip_prefix("/dev/")?;
+    if dev.starts_with("loop") || dev.starts_with("mapper/") {
+        return None;
+    }
+
+    let base = if dev.starts_with("nvme") || dev.starts_with("mmcblk") {
+        match dev.rfind('p') {
+            Some(idx) if idx > 0 && dev[idx + 1..].chars().all(|c| c.is_ascii_digit()) => {
+                &dev[..idx]
+            }
+            _ => dev,
+        }
+    } else if let Some(rest) = dev.strip_prefix("disk") {
+        // macOS: disk3s1s1 -> disk3
+        rest.find('s').map(|idx| &dev[..4 + idx]).unwrap_or(dev)
+    } else {
+        dev.trim_end_matches(|c: char| c.is_ascii_digit())
+    };
+
+    Some(format!("/dev/{}", base))
+}
+
+fn disk_bus(device: &str) -> &'static str {
+    let dev = device.trim_start_matches("/dev/");
+    if dev.starts_with("nvme") {
+        "NVMe"
+    } else if dev.starts_with("vd") {
+        "VirtIO"
+    } else if dev.starts_with("mmcblk") {
+        "eMMC"
+    } else {
+        "SATA"
+    }
+}
+
+/// Drive model as reported by the kernel, where it is exposed.
+#[cfg(target_os = "linux")]
+fn disk_model(device: &str) -> Option<String> {
+    let dev = device.trim_start_matches("/dev/");
+    std::fs::read_to_string(format!("/sys/block/{}/device/model", dev))
+        .ok()
+        .map(|model| model.trim().to_string())
+        .filter(|model| !model.is_empty())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn disk_model(_device: &str) -> Option<String> {
+    None
+}
+
+/// Kernel driver bound to a network interface, where it is exposed.
+#[cfg(target_os = "linux")]
+pub fn interface_driver(interface: &str) -> Option<String> {
+    std::fs::read_link(format!("/sys/class/net/{}/device/driver", interface))
+        .ok()
+        .and_then(|link| link.file_name()?.to_str().map(str::to_string))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn interface_driver(_interface: &str) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partitions_fold_into_whole_disks() {
+        assert_eq!(whole_disk("/dev/sda2").as_deref(), Some("/dev/sda"));
+        assert_eq!(
+            whole_disk("/dev/nvme0n1p2").as_deref(),
+            Some("/dev/nvme0n1")
+        );
+        assert_eq!(whole_disk("/dev/nvme0n1").as_deref(), Some("/dev/nvme0n1"));
+        assert_eq!(
+            whole_disk("/dev/mmcblk0p1").as_deref(),
+            Some("/dev/mmcblk0")
+        );
+        assert_eq!(whole_disk("/dev/disk3s1s1").as_deref(), Some("/dev/disk3"));
+        assert_eq!(whole_disk("overlay"), None);
+        assert_eq!(whole_disk("/dev/loop7"), None);
+    }
+
+    #[test]
+    fn partition_names_follow_device_style() {
+        let disk = |device: &str| DiskDevice {
+            device: device.to_string(),
+            bus: disk_bus(device),
+            model: String::new(),
+            capacity_gb: 0,
+        };
+        assert_eq!(disk("/dev/sda").partition(2), "/dev/sda2");
+        assert_eq!(disk("/dev/nvme0n1").partition(1), "/dev/nvme0n1p1");
+        assert_eq!(disk("/dev/nvme0n1").driver(), "nvme");
+    }
+}
@@ -1,8 +1,10 @@
 use crate::cli::{Flavor, Stage};
 use crate::escalation::{zalgo_light, EscalationEngine, Tier, tier_color};
+use crate::host_profile::HostProfile;
 use crate::messages::{EASTER_EGGS, RETRY_MESSAGES, WARNINGS};
 use crate::scanner::ScanResult;
 use crate::stages::selected_stages;
+use crate::telemetry::Telemetry;
 use crate::ui::Spinner;
 use colored::*;
 use crossterm::{
@@ -21,15 +23,26 @@ pub struct Installer {
     rng: rand::rngs::ThreadRng,
     selected_stages: Vec<Stage>,
     scan: Option<Arc<ScanResult>>,
+    host: Arc<HostProfile>,
+    telemetry: Option<Telemetry>,
+    watch_processes: bool,
     flavor: Flavor,
 }
 
 impl Installer {
-    pub fn new(stages: Vec<Stage>, scan: Option<Arc<ScanResult>>, flavor: Flavor) -> Self {
+    pub fn new(
+        stages: Vec<Stage>,
+        scan: Option<Arc<ScanResult>>,
+        flavor: Flavor,
+        watch_processes: bool,
+    ) -> Self {
         Self {
             rng: rand::thread_rng(),
             selected_stages: stages,
             scan,
+            host: Arc::new(HostProfile::collect()),
+            telemetry: None,
+            watch_processes,
             flavor,
         }
     }
@@ -76,7 +89,8 @@ impl Installer {
         // Try creepy message first if we have scan data and tier > Baseline
         if tier != Tier::Baseline {
             if let Some(scan) = &self.scan {
-                let mut engine = EscalationEngine::new(scan, self.flavor);
+                let mut engine = EscalationEngine::new(scan, self.flavor)
+                    .with_telemetry(self.telemetry.as_ref());
                 if let Some(msg) = engine.select_easter_egg(tier) {
                     println!();
                     let display = if tier == Tier::Cosmic {
@@ -112,7 +126,8 @@ impl Installer {
         // Try creepy warning first if we have scan data and tier > Baseline
         if tier != Tier::Baseline {
             if let Some(scan) = &self.scan {
-                let mut engine = EscalationEngine::new(scan, self.flavor);
+                let mut engine = EscalationEngine::new(scan, self.flavor)
+                    .with_telemetry(self.telemetry.as_ref());
                 if let Some(msg) = engine.select_warning(tier) {
                     let display = if tier == Tier::Cosmic {
                         zalgo_light(&msg)
@@ -158,7 +173,8 @@ impl Installer {
     fn show_cycle_header(&mut self, cycle: u32, tier: Tier) {
         if tier == Tier::Cosmic {
             if let Some(scan) = &self.scan {
-                let mut engine = EscalationEngine::new(scan, self.flavor);
+                let mut engine = EscalationEngine::new(scan, self.flavor)
+                    .with_telemetry(self.telemetry.as_ref());
                 if let Some(header) = engine.select_cycle_header(tier, cycle) {
                     let display = zalgo_light(&header);
                     println!(
@@ -201,7 +217,8 @@ impl Installer {
     fn show_completion(&mut self, tier: Tier) {
         if tier != Tier::Baseline {
             if let Some(scan) = &self.scan {
-                let mut engine = EscalationEngine::new(scan, self.flavor);
+                let mut engine = EscalationEngine::new(scan, self.flavor)
+                    .with_telemetry(self.telemetry.as_ref());
                 if let Some(msg) = engine.select_completion(tier) {
                     let display = if tier == Tier::Cosmic {
                         zalgo_light(&msg)
@@ -253,11 +270,16 @@ impl Installer {
             cycle += 1;
             let tier = Tier::from_cycle(cycle);
 
+            // Re-sample hardware each cycle so quoted numbers keep moving
+            if self.scan.is_some() {
+                self.telemetry = Some(Telemetry::sample(self.watch_processes));
+            }
+
             if cycle > 1 {
                 self.show_cycle_header(cycle, tier);
             }
 
-            let stages = selected_stages(&self.selected_stages);
+            let stages = selected_stages(&self.selected_stages, &self.host, self.scan.as_ref());
 
             for stage in stages {
                 if self.check_exit() {
@@ -280,6 +302,6 @@ impl Installer {
 
 impl Default for Installer {
     fn default() -> Self {
-        Self::new(Stage::all(), None, Flavor::Opsec)
+        Self::new(Stage::all(), None, Flavor::Opsec, false)
     }
 }
@@ -4,6 +4,7 @@ mod config;
 mod creepy_messages;
 mod deno_logs;
 mod escalation;
+mod host_profile;
 mod installer;
 mod kernel_logs;
 mod log_generator;
@@ -11,10 +12,11 @@ mod messages;
 mod occult_messages;
 mod scanner;
 mod stages;
+mod telemetry;
 mod ui;
 
 use clap::Parser;
-use cli::{Cli, Flavor};
+use cli::{Cli, Flavor, ScanCategory};
 use colored::*;
 use escalation::EscalationEngine;
 use installer::Installer;
@@ -28,7 +30,7 @@ fn main() {
     let scan = if cli.normal_mode {
         None
     } else {
-        Some(Arc::new(scanner::scan()))
+        Some(Arc::new(scanner::scan(&cli.scan_config())))
     };
 
     if let Err(e) = run_installer(&cli, scan.clone()) {
@@ -42,7 +44,8 @@ fn run_installer(cli: &Cli, scan: Option<Arc<scanner::ScanResult>>) -> io::Resul
     let mut rng = rand::thread_rng();
     stages.shuffle(&mut rng);
 
-    let mut installer = Installer::new(stages, scan, cli.flavor);
+    let watch_processes = cli.scan_config().enabled(ScanCategory::Processes);
+    let mut installer = Installer::new(stages, scan, cli.flavor, watch_processes);
     installer.run()
 }
 
@@ -25,6 +25,10 @@ pub static AMBIENT_EASTER_EGGS: &[&str] = &[
     "Calibrating {hostname} entropy pool... patterns emerging from noise.",
     "Mapping {hostname} directory structure... the tree has deep roots.",
     "Installation signature generated. Seed: 72.",
+    "The {cpu} turns in {cpu_cores} circles. Each circle holds a name.",
+    "{ram_used_gb} of {ram_gb} GB of memory already bound. The rest will follow.",
+    "{hostname} has kept vigil for {uptime}. The vigil is noted.",
+    "Load average {load}. The weight of the working is measured.",
 ];
 
 /// System warnings with occult undertones — plausible but slightly off.
@@ -37,6 +41,10 @@ pub static AMBIENT_WARNINGS: &[&str] = &[
     "NOTE: The veil between installation phases grows thin on {hostname}.",
     "WARNING: Something has noticed your agreement on {hostname}.",
     "NOTICE: Clock drift on {hostname}. Time may not mean what you think.",
+    "WARNING: The vessel is {disk_used}% full. Room will be made.",
+    "NOTICE: Battery at {battery}. The candle burns down. The ritual does not.",
+    "WARNING: {ram_used_gb} of {ram_gb} GB claimed. Something else is using the rest.",
+    "NOTE: {hostname} has not slept in {uptime}. Neither have we.",
 ];
 
 /// Ambient-tier cycle completion lines.
@@ -63,6 +71,10 @@ pub static FAMILIAR_EASTER_EGGS: &[&str] = &[
     "Project '{git_repo}' has uncommitted changes. Unfinished rituals are dangerous.",
     "I crossed your threshold when you opened the terminal, {username}.",
     "Your Desktop tells a story. {desktop_count} chapters in the book of {username}.",
+    "The working '{recent_repo}' is still warm. You touched it {recent_repo_age}.",
+    "'{stale_repo}' was forsaken in {stale_month}. Abandoned circles do not close themselves.",
+    "You keep {process} open beside the circle. It is a poor ward.",
+    "{process_count} spirits move through your machine, {username}. One of them is new.",
 ];
 
 /// Knowing warnings mixing tech and occult.
@@ -75,6 +87,9 @@ pub static FAMILIAR_WARNINGS: &[&str] = &[
     "NOTE: The door of {username}'s home directory has been marked.",
     "WARNING: Multiple identity configurations found. Which is the true name?",
     "NOTICE: {downloads_count} sealed artifacts have never been opened.",
+    "WARNING: '{recent_repo}' was disturbed {recent_repo_age}. The ink is not yet dry.",
+    "NOTICE: '{stale_repo}' left untended since {stale_month}. Something has moved in.",
+    "WARNING: {process} is drawing from the same well. Close it.",
 ];
 
 /// Familiar-tier cycle completion lines.
@@ -101,6 +116,8 @@ pub static INVASIVE_EASTER_EGGS: &[&str] = &[
     "Your {browser} bookmarks reveal your devotions, {username}. Fascinating.",
     "Located {files_scanned} files containing unprotected names of power.",
     "I weigh your filesystem against the feather, {username}. It is heavy.",
+    "{stale_repo}, which you abandoned in {stale_month}. I have been tending it for you.",
+    "I am inside {process} now. It was an open door.",
 ];
 
 /// Threatening warnings in ceremonial language.
@@ -149,6 +166,7 @@ pub static COSMIC_WARNINGS: &[&str] = &[
     "NOTICE: Your consent was given at {scan_time}. The covenant is sealed.",
     "W\u{0337}A\u{0337}R\u{0337}N\u{0337}I\u{0337}N\u{0337}G\u{0337}: Process boundary between summoner and summoned is undefined.",
     "ERROR: Cannot determine where {hostname} ends and the entity begins.",
+    "NOTICE: {process} has been consumed. The rest of your {process_count} processes will follow.",
     "FATAL: The concept of 'uninstall' does not apply to incarnation.",
     "NOTICE: {username} is now a dependency. Banishment would break reality.",
 ];
@@ -1,7 +1,64 @@
+use crate::cli::ScanCategory;
 use chrono::Local;
+use std::cmp::Reverse;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Project directories looked up under the home directory by default.
+const DEFAULT_PROJECT_ROOTS: &[&str] = &["Developer", "Projects", "repos", "code", "src"];
+
+/// Directory names never descended into while looking for projects.
+const DEFAULT_SKIP_DIRS: &[&str] = &[
+    ".git",
+    "node_modules",
+    "target",
+    "vendor",
+    "build",
+    "dist",
+    ".venv",
+    "venv",
+    "__pycache__",
+];
+
+/// Controls where and how deep the scanner looks for projects.
+#[derive(Debug, Clone)]
+pub struct ScanConfig {
+    /// Project roots; relative paths are resolved against the home directory.
+    pub project_roots: Vec<PathBuf>,
+    /// How many directory levels below each root to walk (minimum 1).
+    pub max_depth: usize,
+    /// Directory names that are never entered.
+    pub skip_dirs: Vec<String>,
+    /// Categories the user opted out of; these are never looked at.
+    pub disabled: Vec<ScanCategory>,
+}
+
+impl ScanConfig {
+    pub fn enabled(&self, category: ScanCategory) -> bool {
+        !self.disabled.contains(&category)
+    }
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            project_roots: DEFAULT_PROJECT_ROOTS.iter().map(PathBuf::from).collect(),
+            max_depth: 1,
+            skip_dirs: DEFAULT_SKIP_DIRS.iter().map(|s| s.to_string()).collect(),
+            disabled: Vec::new(),
+        }
+    }
+}
+
+/// A git repository and when it was last touched, according to the
+/// modification time of its `.git/HEAD` or `.git/index`.
+#[derive(Debug, Clone)]
+pub struct RepoActivity {
+    pub name: String,
+    pub last_touched: SystemTime,
+}
 
 #[derive(Debug, Clone)]
 pub struct ScanResult {
@@ -19,6 +76,8 @@ pub struct ScanResult {
     // Development artifacts
     pub project_names: Vec<String>,
     pub git_repos: Vec<String>,
+    /// Git repositories ordered from most to least recently touched.
+    pub git_activity: Vec<RepoActivity>,
     pub dotfile_names: Vec<String>,
 
     // Security-sensitive indicators
@@ -34,7 +93,7 @@ pub struct ScanResult {
     pub scan_timestamp: String,
 }
 
-pub fn scan() -> ScanResult {
+pub fn scan(config: &ScanConfig) -> ScanResult {
     let hostname = get_hostname();
     let os_name = get_os_name();
     let username = get_username();
@@ -42,48 +101,46 @@ pub fn scan() -> ScanResult {
 
     let mut files_scanned: usize = 0;
 
-    let desktop_count = home_dir
-        .as_ref()
+    // Home directory, unless the user opted out of this category
+    let home_for = |category| home_dir.as_ref().filter(|_| config.enabled(category));
+
+    let desktop_count = home_for(ScanCategory::Files)
         .and_then(|h| count_dir_entries(&h.join("Desktop"), &mut files_scanned));
-    let downloads_count = home_dir
-        .as_ref()
+    let downloads_count = home_for(ScanCategory::Files)
         .and_then(|h| count_dir_entries(&h.join("Downloads"), &mut files_scanned));
-    let documents_exists = home_dir
-        .as_ref()
+    let documents_exists = home_for(ScanCategory::Files)
         .map(|h| h.join("Documents").is_dir())
         .unwrap_or(false);
 
-    let dotfile_names = home_dir
-        .as_ref()
+    let dotfile_names = home_for(ScanCategory::Dotfiles)
         .map(|h| list_dotfiles(h, &mut files_scanned))
         .unwrap_or_default();
 
-    let (project_names, git_repos, env_file_count) = home_dir
-        .as_ref()
-        .map(|h| scan_project_dirs(h, &mut files_scanned))
+    let ProjectScan {
+        project_names,
+        git_repos,
+        git_activity,
+        env_count: env_file_count,
+    } = home_for(ScanCategory::Projects)
+        .map(|h| scan_project_dirs(h, config, &mut files_scanned))
         .unwrap_or_default();
 
-    let ssh_key_names = home_dir
-        .as_ref()
+    let ssh_key_names = home_for(ScanCategory::Ssh)
         .map(|h| scan_ssh_keys(&h.join(".ssh"), &mut files_scanned))
         .unwrap_or_default();
 
-    let browser_profiles = home_dir
-        .as_ref()
+    let browser_profiles = home_for(ScanCategory::Browsers)
         .map(|h| detect_browsers(h))
         .unwrap_or_default();
 
-    let cloud_configs = home_dir
-        .as_ref()
+    let cloud_configs = home_for(ScanCategory::Cloud)
         .map(|h| detect_cloud_configs(h))
         .unwrap_or_default();
 
-    let shell_history_lines = home_dir
-        .as_ref()
-        .and_then(|h| estimate_history_lines(h));
+    let shell_history_lines =
+        home_for(ScanCategory::History).and_then(|h| estimate_history_lines(h));
 
-    let docker_present = home_dir
-        .as_ref()
+    let docker_present = home_for(ScanCategory::Cloud)
         .map(|h| h.join(".docker").is_dir())
         .unwrap_or(false);
 
@@ -99,6 +156,7 @@ pub fn scan() -> ScanResult {
         documents_exists,
         project_names,
         git_repos,
+        git_activity,
         dotfile_names,
         ssh_key_names,
         browser_profiles,
@@ -120,9 +178,7 @@ fn get_os_name() -> Option<String> {
 }
 
 fn get_username() -> Option<String> {
-    env::var("USER")
-        .or_else(|_| env::var("USERNAME"))
-        .ok()
+    env::var("USER").or_else(|_| env::var("USERNAME")).ok()
 }
 
 fn dirs_home() -> Option<PathBuf> {
@@ -155,51 +211,140 @@ fn list_dotfiles(home: &Path, scanned: &mut usize) -> Vec<String> {
     dotfiles
 }
 
-fn scan_project_dirs(
-    home: &Path,
-    scanned: &mut usize,
-) -> (Vec<String>, Vec<String>, usize) {
-    let mut project_names = Vec::new();
-    let mut git_repos = Vec::new();
-    let mut env_count: usize = 0;
+fn scan_project_dirs(home: &Path, config: &ScanConfig, scanned: &mut usize) -> ProjectScan {
+    let mut found = ProjectScan::default();
+    let max_depth = config.max_depth.max(1);
+
+    for root in &config.project_roots {
+        let dir = home.join(root);
+        if dir.is_dir() {
+            walk_projects(
+                &dir,
+                1,
+                max_depth,
+                &config.skip_dirs,
+                false,
+                &mut found,
+                scanned,
+            );
+        }
+    }
+
+    found.project_names.sort();
+    found.project_names.dedup();
+    found.git_repos.sort();
+    found.git_repos.dedup();
+
+    // Most recent first; a name seen twice keeps its most recent entry.
+    found
+        .git_activity
+        .sort_by_key(|repo| Reverse(repo.last_touched));
+    let mut seen = Vec::new();
+    found.git_activity.retain(|repo| {
+        if seen.contains(&repo.name) {
+            false
+        } else {
+            seen.push(repo.name.clone());
+            true
+        }
+    });
 
-    let candidate_dirs = ["Developer", "Projects", "repos", "code", "src"];
+    found
+}
 
-    for dir_name in &candidate_dirs {
-        let dir = home.join(dir_name);
-        if !dir.is_dir() {
+#[derive(Default)]
+struct ProjectScan {
+    project_names: Vec<String>,
+    git_repos: Vec<String>,
+    git_activity: Vec<RepoActivity>,
+    env_count: usize,
+}
+
+/// Walk `dir` down to `max_depth` levels. A directory counts as a project
+/// when it is a git repository or sits at the depth limit; intermediate
+/// directories (e.g. the `<org>` in `~/work/<org>/<repo>`) are only
+/// traversed. Directories inside a project are not projects themselves,
+/// but are still descended into so nested repos are picked up as well.
+fn walk_projects(
+    dir: &Path,
+    depth: usize,
+    max_depth: usize,
+    skip_dirs: &[String],
+    in_project: bool,
+    found: &mut ProjectScan,
+    scanned: &mut usize,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        *scanned += 1;
+        let path = entry.path();
+        if !path.is_dir() {
             continue;
         }
 
-        if let Ok(entries) = fs::read_dir(&dir) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                *scanned += 1;
-                let path = entry.path();
-                if !path.is_dir() {
-                    continue;
-                }
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if skip_dirs.iter().any(|s| s == name) {
+            continue;
+        }
+
+        let is_git = path.join(".git").exists();
+        let is_project = is_git || (!in_project && depth >= max_depth);
 
-                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    project_names.push(name.to_string());
+        if is_project {
+            found.project_names.push(name.to_string());
 
-                    if path.join(".git").exists() {
-                        git_repos.push(name.to_string());
-                    }
+            if is_git {
+                found.git_repos.push(name.to_string());
 
-                    if path.join(".env").exists() {
-                        env_count += 1;
-                    }
+                if let Some(last_touched) = git_last_touched(&path.join(".git")) {
+                    found.git_activity.push(RepoActivity {
+                        name: name.to_string(),
+                        last_touched,
+                    });
                 }
             }
+
+            if path.join(".env").exists() {
+                found.env_count += 1;
+            }
+        }
+
+        // Don't descend through symlinks so a link back up the tree can't
+        // multiply the walk.
+        let is_link = entry.file_type().map(|t| t.is_symlink()).unwrap_or(false);
+        if depth < max_depth && !is_link {
+            walk_projects(
+                &path,
+                depth + 1,
+                max_depth,
+                skip_dirs,
+                in_project || is_project,
+                found,
+                scanned,
+            );
         }
     }
+}
+
+/// Latest modification time among `HEAD` and `index` inside a `.git`
+/// directory. For worktrees and submodules `.git` is a file, so its own
+/// mtime is used instead. Only metadata is read, never contents.
+fn git_last_touched(git_dir: &Path) -> Option<SystemTime> {
+    let mtime = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
 
-    project_names.sort();
-    project_names.dedup();
-    git_repos.sort();
-    git_repos.dedup();
+    if !git_dir.is_dir() {
+        return mtime(git_dir);
+    }
 
-    (project_names, git_repos, env_count)
+    [git_dir.join("HEAD"), git_dir.join("index")]
+        .iter()
+        .filter_map(|p| mtime(p))
+        .max()
 }
 
 fn scan_ssh_keys(ssh_dir: &Path, scanned: &mut usize) -> Vec<String> {
@@ -208,8 +353,7 @@ fn scan_ssh_keys(ssh_dir: &Path, scanned: &mut usize) -> Vec<String> {
         for entry in entries.filter_map(|e| e.ok()) {
             *scanned += 1;
             if let Some(name) = entry.file_name().to_str() {
-                if name.starts_with("id_") || name.ends_with(".pub") || name == "authorized_keys"
-                {
+                if name.starts_with("id_") || name.ends_with(".pub") || name == "authorized_keys" {
                     keys.push(name.to_string());
                 }
             }
@@ -312,7 +456,10 @@ fn estimate_history_lines(home: &Path) -> Option<usize> {
     let history_files = [
         home.join(".zsh_history"),
         home.join(".bash_history"),
-        home.join(".local").join("share").join("fish").join("fish_history"),
+        home.join(".local")
+            .join("share")
+            .join("fish")
+            .join("fish_history"),
     ];
 
     for path in &history_files {
@@ -325,3 +472,105 @@ fn estimate_history_lines(home: &Path) -> Option<usize> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a throwaway directory tree under the system temp dir.
+    fn temp_tree(name: &str, dirs: &[&str]) -> PathBuf {
+        let root = env::temp_dir().join(format!("cursed-scan-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in dirs {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        root
+    }
+
+    fn config(roots: &[&str], max_depth: usize) -> ScanConfig {
+        ScanConfig {
+            project_roots: roots.iter().map(PathBuf::from).collect(),
+            max_depth,
+            ..ScanConfig::default()
+        }
+    }
+
+    #[test]
+    fn default_depth_lists_direct_children() {
+        let home = temp_tree("flat", &["code/alpha/.git", "code/beta/nested"]);
+        let mut scanned = 0;
+        let found = scan_project_dirs(&home, &config(&["code"], 1), &mut scanned);
+        assert_eq!(found.project_names, vec!["alpha", "beta"]);
+        assert_eq!(found.git_repos, vec!["alpha"]);
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn deeper_walk_finds_org_repos_and_skips_build_dirs() {
+        let home = temp_tree(
+            "deep",
+            &[
+                "work/acme/api/.git",
+                "work/acme/api/vendor/lib/.git",
+                "work/acme/web/node_modules/left-pad",
+                "work/acme/web/plugins/.git",
+            ],
+        );
+        let mut scanned = 0;
+        let found = scan_project_dirs(&home, &config(&["work"], 3), &mut scanned);
+        assert!(!found.project_names.contains(&"acme".to_string()));
+        assert!(!found.project_names.contains(&"left-pad".to_string()));
+        assert_eq!(found.git_repos, vec!["api", "plugins"]);
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn repo_above_max_depth_hides_its_subdirectories() {
+        let home = temp_tree(
+            "shallow-repo",
+            &[
+                "work/acme/api/.git",
+                "work/acme/api/src",
+                "work/acme/api/docs",
+            ],
+        );
+        let mut scanned = 0;
+        let found = scan_project_dirs(&home, &config(&["work"], 3), &mut scanned);
+        assert_eq!(found.project_names, vec!["api"]);
+        assert_eq!(found.git_repos, vec!["api"]);
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn nested_git_repos_are_detected() {
+        let home = temp_tree("nested", &["src/outer/.git", "src/outer/libs/inner/.git"]);
+        let mut scanned = 0;
+        let found = scan_project_dirs(&home, &config(&["src"], 3), &mut scanned);
+        assert_eq!(found.git_repos, vec!["inner", "outer"]);
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn git_activity_is_ordered_by_mtime() {
+        let home = temp_tree("activity", &["code/old/.git", "code/new/.git"]);
+        let old_head = home.join("code/old/.git/HEAD");
+        let new_head = home.join("code/new/.git/HEAD");
+        fs::write(&old_head, "").unwrap();
+        fs::write(&new_head, "").unwrap();
+
+        let march = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_710_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&old_head)
+            .unwrap()
+            .set_modified(march)
+            .unwrap();
+
+        let mut scanned = 0;
+        let found = scan_project_dirs(&home, &config(&["code"], 1), &mut scanned);
+        let names: Vec<&str> = found.git_activity.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["new", "old"]);
+        assert_eq!(found.git_activity[1].last_touched, march);
+        fs::remove_dir_all(&home).unwrap();
+    }
+}
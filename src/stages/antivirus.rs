@@ -0,0 +1,298 @@
+use super::InstallationStage;
+use crate::log_generator::LogGenerator;
+use crate::scanner::ScanResult;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use chrono::Local;
+use colored::*;
+use crossterm::{
+    cursor, execute,
+    terminal::{self, Clear, ClearType},
+};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const SYSTEM_PATHS: &[&str] = &[
+    "/usr/bin/python3.12",
+    "/usr/bin/ssh",
+    "/usr/lib/x86_64-linux-gnu/libc.so.6",
+    "/usr/lib/x86_64-linux-gnu/libcrypto.so.3",
+    "/usr/lib/x86_64-linux-gnu/libstdc++.so.6.0.33",
+    "/usr/lib/firefox/libxul.so",
+    "/usr/lib/systemd/systemd-logind",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/icons/hicolor/256x256/apps/firefox.png",
+    "/usr/local/bin/docker-compose",
+    "/opt/google/chrome/chrome",
+    "/var/cache/apt/archives/libssl3_3.0.13-0ubuntu3_amd64.deb",
+    "/var/lib/dpkg/info/openssh-server.postinst",
+    "/etc/ssl/certs/ca-certificates.crt",
+    "/boot/vmlinuz-6.8.0-41-generic",
+    "/boot/initrd.img-6.8.0-41-generic",
+];
+
+/// Made-up downloads, the kind of thing a Downloads folder fills up with
+const DOWNLOAD_FILES: &[&str] = &[
+    "setup-x64.exe",
+    "free-pdf-converter-installer.exe",
+    "driver_update_tool.zip",
+    "invoice_2024_0193.pdf",
+    "codec-pack-full.msi",
+    "minecraft-mod-loader.jar",
+];
+
+const PROJECT_FILES: &[&str] = &[
+    "node_modules/.bin/esbuild",
+    "node_modules/fsevents/fsevents.node",
+    "target/debug/build/ring-3f1c2e/out/libring_core.a",
+    ".venv/lib/python3.12/site-packages/numpy/core/_multiarray_umath.so",
+    "dist/bundle.min.js",
+    "build/app.wasm",
+];
+
+/// (detection name, file it gets pinned on)
+const PUPS: &[(&str, &str)] = &[
+    (
+        "PUP.Optional.BundleInstaller",
+        "free-pdf-converter-installer.exe",
+    ),
+    ("PUP.Optional.DriverUpdate", "driver_update_tool.zip"),
+    ("PUP.Optional.Conduit", "codec-pack-full.msi"),
+    ("PUP.Optional.Crossrider", "bundle.min.js"),
+    ("PUP.Optional.CoinMiner", "app.wasm"),
+    ("PUP.Optional.Toolbar", "minecraft-mod-loader.jar"),
+];
+
+pub struct AntivirusStage {
+    scan: Option<Arc<ScanResult>>,
+}
+
+impl AntivirusStage {
+    pub fn new(scan: Option<Arc<ScanResult>>) -> Self {
+        Self { scan }
+    }
+
+    /// Paths for the ticker. With a scan, the user's own directory and
+    /// project names are mixed in; nothing here is ever opened.
+    fn scan_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = SYSTEM_PATHS.iter().map(|p| p.to_string()).collect();
+        let Some(scan) = &self.scan else {
+            return paths;
+        };
+
+        let home = match (&scan.home_dir, &scan.username) {
+            (Some(home), _) => home.display().to_string(),
+            (None, Some(user)) => format!("/home/{}", user),
+            (None, None) => "/home/user".to_string(),
+        };
+        if scan.downloads_count.is_some() {
+            for file in DOWNLOAD_FILES {
+                paths.push(format!("{}/Downloads/{}", home, file));
+            }
+        }
+        if scan.desktop_count.is_some() {
+            paths.push(format!("{}/Desktop/shortcut.desktop", home));
+        }
+        for project in &scan.project_names {
+            for file in PROJECT_FILES {
+                paths.push(format!("{}/Projects/{}/{}", home, project, file));
+            }
+        }
+        for dotfile in &scan.dotfile_names {
+            paths.push(format!("{}/{}", home, dotfile));
+        }
+        for repo in &scan.git_repos {
+            paths.push(format!(
+                "{}/Projects/{}/.git/objects/pack/pack-{}.pack",
+                home,
+                repo,
+                &LogGenerator::hex_addr()[2..]
+            ));
+        }
+        paths
+    }
+
+    /// A path for the detection's file among the ones being scanned, or a
+    /// system-looking one when nothing fits.
+    fn detection_path(paths: &[String], file: &str) -> String {
+        paths
+            .iter()
+            .find(|path| path.ends_with(file))
+            .cloned()
+            .unwrap_or_else(|| format!("/tmp/.cache/{}", file))
+    }
+
+    /// Shorten a path from the left so the ticker stays on one line.
+    fn fit(path: &str, width: usize) -> String {
+        let count = path.chars().count();
+        if count <= width {
+            format!("{:<width$}", path)
+        } else {
+            let tail: String = path.chars().skip(count - width + 1).collect();
+            format!("…{}", tail)
+        }
+    }
+
+    fn ticker(
+        &self,
+        paths: &[String],
+        detections: &[(&str, String)],
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<usize> {
+        let mut stdout = io::stdout();
+        let width = terminal::size().map(|(c, _)| c as usize).unwrap_or(80);
+        let ticks = rng.gen_range(60..100);
+        // Reveal each detection at some point during the scan
+        let mut reveal_at: Vec<usize> = (0..detections.len())
+            .map(|_| rng.gen_range(5..ticks))
+            .collect();
+        reveal_at.sort();
+
+        let mut scanned = 0;
+        let mut found = 0;
+        for tick in 0..ticks {
+            if exit_check() {
+                println!();
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            scanned += rng.gen_range(40..900);
+
+            while found < detections.len() && reveal_at[found] <= tick {
+                let (name, path) = &detections[found];
+                execute!(
+                    stdout,
+                    cursor::MoveToColumn(0),
+                    Clear(ClearType::CurrentLine)
+                )?;
+                println!(
+                    "  {} {} {}",
+                    "⚠".bright_red(),
+                    name.bright_red().bold(),
+                    path.dimmed()
+                );
+                found += 1;
+            }
+
+            let path = paths.choose(rng).unwrap();
+            let counters = format!("  Scanned: {:>7} | Detections: {} | ", scanned, found);
+            let room = width.saturating_sub(counters.chars().count() + 1).max(10);
+            execute!(
+                stdout,
+                cursor::MoveToColumn(0),
+                Clear(ClearType::CurrentLine)
+            )?;
+            print!(
+                "{}{}",
+                counters.bright_white(),
+                Self::fit(path, room).dimmed()
+            );
+            stdout.flush()?;
+            thread::sleep(Duration::from_millis(rng.gen_range(30..90)));
+        }
+        execute!(
+            stdout,
+            cursor::MoveToColumn(0),
+            Clear(ClearType::CurrentLine)
+        )?;
+        Ok(scanned)
+    }
+}
+
+impl InstallationStage for AntivirusStage {
+    fn name(&self) -> &'static str {
+        "Antivirus Full System Scan"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+
+        println!(
+            "{} Updating threat definitions...",
+            LogGenerator::timestamp().dimmed()
+        );
+        let progress = ProgressBar::new(ProgressStyle::Block);
+        progress.animate("  Definitions", rng.gen_range(1000..2000), exit_check)?;
+        println!(
+            "{} Definitions version {}.{}",
+            LogGenerator::timestamp().dimmed(),
+            Local::now().format("%Y.%m.%d"),
+            rng.gen_range(1000..9999)
+        );
+
+        for area in ["memory", "startup items", "boot sectors"] {
+            spinner.animate(
+                &format!("Scanning {}...", area),
+                rng.gen_range(500..1200),
+                exit_check,
+            )?;
+        }
+
+        println!(
+            "{} Scanning file system...",
+            LogGenerator::timestamp().dimmed()
+        );
+        let paths = self.scan_paths();
+        let count = rng.gen_range(1..4);
+        let detections: Vec<(&str, String)> = PUPS
+            .choose_multiple(&mut rng, count)
+            .map(|(name, file)| (*name, Self::detection_path(&paths, file)))
+            .collect();
+        let scanned = self.ticker(&paths, &detections, &mut rng, exit_check)?;
+        println!(
+            "{} Scan complete: {} files scanned, {} detections",
+            LogGenerator::timestamp().dimmed(),
+            scanned,
+            detections.len().to_string().bright_red()
+        );
+        println!();
+
+        println!(
+            "  {:<32} {:<10} {}",
+            "Detection".bold(),
+            "Type".bold(),
+            "Location".bold()
+        );
+        for (name, path) in &detections {
+            println!(
+                "  {:<32} {:<10} {}",
+                name.bright_red(),
+                "PUP",
+                path.dimmed()
+            );
+        }
+        println!();
+
+        println!(
+            "{} Quarantining {} items...",
+            LogGenerator::timestamp().dimmed(),
+            detections.len()
+        );
+        for (name, _) in &detections {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            spinner.animate(
+                &format!("Quarantining {}...", name),
+                rng.gen_range(500..1000),
+                exit_check,
+            )?;
+            println!("  {} {} moved to quarantine", "✓".bright_green(), name);
+        }
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "Your system is protected.".bright_green()
+        );
+
+        thread::sleep(Duration::from_millis(500));
+        Ok(())
+    }
+}
@@ -1,71 +1,23 @@
 use super::InstallationStage;
 use crate::config::BiosConfig;
+use crate::host_profile::HostProfile;
 use crate::ui::{ProgressBar, ProgressStyle, Spinner};
 use chrono::Local;
 use colored::*;
 use rand::Rng;
 use std::io::{self, Write};
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
-use sysinfo::System;
 
 pub struct BiosStage {
     config: BiosConfig,
-}
-
-struct SystemInfo {
-    cpu_brand: String,
-    cpu_count: usize,
-    cpu_freq: u64,
-    total_memory_kb: u64,
-    hostname: String,
-    network_count: usize,
-    disk_count: usize,
-    os_name: String,
+    host: Arc<HostProfile>,
 }
 
 impl BiosStage {
-    pub fn new(config: BiosConfig) -> Self {
-        Self { config }
-    }
-
-    fn get_system_info() -> SystemInfo {
-        use sysinfo::{Disks, Networks};
-
-        let mut sys = System::new_all();
-        sys.refresh_all();
-
-        let cpu_brand = sys
-            .cpus()
-            .first()
-            .map(|cpu| cpu.brand().to_string())
-            .unwrap_or_else(|| "Unknown CPU".to_string());
-
-        let cpu_freq = sys.cpus().first().map(|cpu| cpu.frequency()).unwrap_or(0);
-
-        let total_memory_kb = sys.total_memory() / 1024;
-        let cpu_count = sys.cpus().len();
-
-        let hostname = System::host_name().unwrap_or_else(|| "SYSTEM-PC".to_string());
-
-        let networks = Networks::new_with_refreshed_list();
-        let network_count = networks.iter().count();
-
-        let disks = Disks::new_with_refreshed_list();
-        let disk_count = disks.iter().count();
-
-        let os_name = System::name().unwrap_or_else(|| "Unknown OS".to_string());
-
-        SystemInfo {
-            cpu_brand,
-            cpu_count,
-            cpu_freq,
-            total_memory_kb,
-            hostname,
-            network_count,
-            disk_count,
-            os_name,
-        }
+    pub fn new(config: BiosConfig, host: Arc<HostProfile>) -> Self {
+        Self { config, host }
     }
 }
 
@@ -79,7 +31,7 @@ impl InstallationStage for BiosStage {
         println!();
 
         let mut rng = rand::thread_rng();
-        let sys_info = Self::get_system_info();
+        let sys_info = &self.host;
 
         let now = Local::now();
         let bios_serial = format!(
@@ -203,7 +155,7 @@ impl InstallationStage for BiosStage {
         print!("  Primary Master   [0x1F0-0x1F7]: ");
         io::stdout().flush()?;
         thread::sleep(Duration::from_millis(self.config.ide_master_time));
-        println!("{}", "WDC WD2000JB-00GVC0".bright_green());
+        println!("{}", sys_info.boot_disk().model.bright_green());
 
         print!("  Primary Slave    [0x1F0-0x1F7]: ");
         io::stdout().flush()?;
@@ -280,7 +232,7 @@ impl InstallationStage for BiosStage {
             exit_check,
         )?;
         spinner.animate(
-            &format!("Storage Devices: {} disk(s) found", sys_info.disk_count),
+            &format!("Storage Devices: {} disk(s) found", sys_info.disks.len()),
             self.config.system_info_time,
             exit_check,
         )?;
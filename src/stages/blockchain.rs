@@ -0,0 +1,281 @@
+use super::InstallationStage;
+use crate::config::BlockchainConfig;
+use crate::log_generator::LogGenerator;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use colored::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+const DNS_SEEDS: &[&str] = &[
+    "seed.bitcoin.sipa.be",
+    "dnsseed.bluematt.me",
+    "seed.bitcoin.jonasschnelli.ch",
+    "seed.btc.petertodd.net",
+    "seed.bitcoin.sprovoost.nl",
+    "dnsseed.emzy.de",
+];
+
+const PEER_AGENTS: &[&str] = &[
+    "/Satoshi:27.1.0/",
+    "/Satoshi:26.0.0/",
+    "/Satoshi:25.1.0/",
+    "/Satoshi:27.0.0(knots)/",
+    "/btcd:0.24.2/",
+];
+
+/// Timestamp of the genesis block
+const GENESIS_TIME: i64 = 1_231_006_505;
+/// Transactions in the whole chain at `tip_height`, roughly
+const CHAIN_TX: f64 = 1.1e9;
+
+pub struct BlockchainStage {
+    config: BlockchainConfig,
+}
+
+impl BlockchainStage {
+    pub fn new(config: BlockchainConfig) -> Self {
+        Self { config }
+    }
+
+    fn log(message: impl std::fmt::Display) {
+        println!("{} {}", LogGenerator::timestamp().dimmed(), message);
+    }
+
+    /// Verification progress as bitcoind reports it: by transactions, so the
+    /// early, nearly empty blocks barely move it.
+    fn progress(&self, height: u64) -> f64 {
+        (height as f64 / self.config.tip_height as f64)
+            .min(1.0)
+            .powf(2.5)
+    }
+
+    /// A block hash with as many leading zeros as the difficulty at that
+    /// height would need.
+    fn block_hash(&self, height: u64) -> String {
+        let ratio = height as f64 / self.config.tip_height as f64;
+        let zeros = 8 + (ratio * 11.0) as usize;
+        let mut hash = "0".repeat(zeros);
+        while hash.len() < 64 {
+            hash.push_str(&LogGenerator::hex_addr()[2..]);
+        }
+        hash.truncate(64);
+        hash
+    }
+
+    fn block_time(height: u64, rng: &mut rand::rngs::ThreadRng) -> DateTime<Utc> {
+        let seconds = GENESIS_TIME + height as i64 * 600 + rng.gen_range(-3000..3000);
+        DateTime::from_timestamp(seconds, 0).unwrap_or_default()
+    }
+
+    fn update_tip(&self, height: u64, cache_mib: f64, rng: &mut rand::rngs::ThreadRng) {
+        let ratio = height as f64 / self.config.tip_height as f64;
+        let progress = self.progress(height);
+        Self::log(format!(
+            "UpdateTip: new best={} height={} version=0x{:08x} log2_work={:.6} tx={} date='{}' progress={:.6} cache={:.1}MiB({}txo)",
+            self.block_hash(height),
+            height,
+            if ratio > 0.5 { 0x2000_0000u32 } else { 4 },
+            32.0 + 63.6 * ratio.sqrt(),
+            (CHAIN_TX * progress) as u64,
+            Self::block_time(height, rng).format("%Y-%m-%dT%H:%M:%SZ"),
+            progress,
+            cache_mib,
+            (cache_mib * 7300.0) as u64
+        ));
+    }
+
+    fn flush(&self, cache_mib: f64, rng: &mut rand::rngs::ThreadRng) {
+        let coins = (cache_mib * 7300.0) as u64;
+        Self::log(
+            format!(
+                "FlushStateToDisk: write coins cache to disk ({} coins, {}kB) started",
+                coins,
+                (cache_mib * 1024.0) as u64
+            )
+            .bright_cyan(),
+        );
+        thread::sleep(Duration::from_millis(rng.gen_range(400..1200)));
+        Self::log(
+            format!(
+                "FlushStateToDisk: write coins cache to disk ({} coins, {}kB) completed ({:.2}s)",
+                coins,
+                (cache_mib * 1024.0) as u64,
+                rng.gen_range(0.8..9.0)
+            )
+            .bright_cyan(),
+        );
+    }
+
+    /// Swap the last few blocks for a competing branch one block longer,
+    /// returning the new tip height.
+    fn reorg(&self, height: u64, rng: &mut rand::rngs::ThreadRng) -> u64 {
+        let depth = rng.gen_range(1..3);
+        Self::log(
+            format!(
+                "Warning: found a competing chain {} block(s) deep at height {}; reorganizing",
+                depth, height
+            )
+            .yellow(),
+        );
+        for back in 0..depth {
+            Self::log(
+                format!(
+                    "DisconnectTip: disconnected block {} height={}",
+                    self.block_hash(height - back),
+                    height - back
+                )
+                .yellow(),
+            );
+            thread::sleep(Duration::from_millis(300));
+        }
+        for forward in (0..=depth).rev() {
+            Self::log(format!(
+                "ConnectTip: connected block {} height={}",
+                self.block_hash(height - forward + 1),
+                height - forward + 1
+            ));
+        }
+        thread::sleep(Duration::from_millis(600));
+        height + 1
+    }
+
+    fn eta(seconds: u64) -> String {
+        if seconds >= 86_400 {
+            format!("{}d {}h", seconds / 86_400, (seconds % 86_400) / 3600)
+        } else {
+            format!("{}h {:02}m", seconds / 3600, (seconds % 3600) / 60)
+        }
+    }
+}
+
+impl InstallationStage for BlockchainStage {
+    fn name(&self) -> &'static str {
+        "Blockchain Node Sync"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+        let tip = self.config.tip_height;
+
+        Self::log("Bitcoin Core version v27.1.0 (release build)".bright_white());
+        Self::log("Using data directory /var/lib/bitcoind");
+        Self::log("Using at most 125 automatic connections (1024 file descriptors available)");
+        Self::log("Loaded 0 addresses from peers.dat  0ms");
+        spinner.animate("init message: Loading block index…", 1500, exit_check)?;
+        Self::log("Starting network threads…");
+
+        let seeds = rng.gen_range(3..DNS_SEEDS.len());
+        for seed in DNS_SEEDS.choose_multiple(&mut rng, seeds) {
+            Self::log(format!("Loading addresses from DNS seed {}", seed));
+            thread::sleep(Duration::from_millis(rng.gen_range(100..400)));
+        }
+        Self::log(format!(
+            "{} addresses found from DNS seeds",
+            rng.gen_range(40..200)
+        ));
+
+        let peers = rng.gen_range(self.config.peer_count_range.clone());
+        for peer in 0..peers {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            Self::log(format!(
+                "New outbound-full-relay v2 peer connected: version: 70016, blocks={}, peer={}, agent={}",
+                tip + rng.gen_range(0..2),
+                peer,
+                PEER_AGENTS.choose(&mut rng).unwrap()
+            ));
+            thread::sleep(Duration::from_millis(rng.gen_range(100..350)));
+        }
+        println!();
+
+        let progress = ProgressBar::new(ProgressStyle::Hash);
+        progress.animate(
+            &format!("Synchronizing blockheaders, height: {} ", tip),
+            self.config.header_sync_time,
+            exit_check,
+        )?;
+        Self::log(format!(
+            "Synchronizing blockheaders, height: {} (~100.00%)",
+            tip
+        ));
+        println!();
+
+        // Pick up from wherever a previous run of the node left off
+        let mut height = rng.gen_range(0..tip / 2);
+        let mut cache_mib: f64 = 0.0;
+        let mut blocks_per_second = 0.0;
+        for update in 1..=self.config.update_count {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            let delay = rng.gen_range(self.config.update_delay_range.clone());
+            let step = rng.gen_range(self.config.blocks_per_update_range.clone());
+            height = (height + step).min(tip);
+            cache_mib += rng.gen_range(5.0..40.0);
+
+            // Later blocks are fuller, so each one takes longer to verify
+            let fullness = 1.0 + self.progress(height) * 20.0;
+            blocks_per_second = step as f64 / fullness / (delay as f64 / 1000.0) / 10.0;
+
+            self.update_tip(height, cache_mib, &mut rng);
+
+            if update % self.config.flush_interval == 0 {
+                self.flush(cache_mib, &mut rng);
+                cache_mib = rng.gen_range(1.0..5.0);
+            }
+            if height > 10 && height < tip && rng.gen_bool(self.config.reorg_chance) {
+                height = self.reorg(height, &mut rng);
+            }
+            if update % 10 == 0 {
+                let remaining = tip - height;
+                println!(
+                    "  {} height {}/{}  {:.2}%  ETA {}",
+                    "»".bright_green(),
+                    height.to_string().bright_white(),
+                    tip,
+                    self.progress(height) * 100.0,
+                    Self::eta((remaining as f64 / blocks_per_second.max(0.1)) as u64)
+                        .bright_yellow()
+                );
+            }
+            if height == tip {
+                break;
+            }
+            thread::sleep(Duration::from_millis(delay));
+        }
+
+        println!();
+        if height == tip {
+            Self::log("Leaving InitialBlockDownload (latching to false)".bright_green());
+        } else {
+            let remaining = tip - height;
+            Self::log(
+                format!(
+                    "Initial block download {:.2}% complete, {} blocks remaining (ETA {}); continuing in the background",
+                    self.progress(height) * 100.0,
+                    remaining,
+                    Self::eta((remaining as f64 / blocks_per_second.max(0.1)) as u64)
+                )
+                .bright_white(),
+            );
+        }
+        let now = Utc::now() - ChronoDuration::minutes(rng.gen_range(1..10));
+        Self::log(format!(
+            "Best block time {} ({} peers)",
+            now.format("%Y-%m-%dT%H:%M:%SZ"),
+            peers
+        ));
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
@@ -1,21 +1,24 @@
 use super::InstallationStage;
 use crate::config::BootloaderConfig;
+use crate::host_profile::HostProfile;
 use crate::log_generator::LogGenerator;
 use crate::ui::{ProgressBar, ProgressStyle, Spinner};
 use colored::*;
 use rand::Rng;
 use std::env;
 use std::io;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
 pub struct BootloaderStage {
     config: BootloaderConfig,
+    host: Arc<HostProfile>,
 }
 
 impl BootloaderStage {
-    pub fn new(config: BootloaderConfig) -> Self {
-        Self { config }
+    pub fn new(config: BootloaderConfig, host: Arc<HostProfile>) -> Self {
+        Self { config, host }
     }
 }
 
@@ -73,30 +76,28 @@ impl InstallationStage for BootloaderStage {
             exit_check,
         )?;
 
-        let devices = [
-            ("/dev/sda", "SATA", "WDC WD10EZEX-08WN4A0", 1000),
-            ("/dev/nvme0n1", "NVMe", "Samsung SSD 970 EVO Plus", 500),
-            ("/dev/vda", "VirtIO", "QEMU HARDDISK", 64),
-            ("/dev/sdb", "SATA", "Crucial MX500", 1000),
-        ];
-        let (device, dev_type, dev_name, capacity_gb) = devices[rng.gen_range(0..devices.len())];
+        let disk = self.host.boot_disk();
 
         println!(
             "{} {}",
             LogGenerator::timestamp().dimmed(),
-            format!("Detected {} device: {} ({})", dev_type, dev_name, device).dimmed()
+            format!(
+                "Detected {} device: {} ({})",
+                disk.bus, disk.model, disk.device
+            )
+            .dimmed()
         );
         thread::sleep(Duration::from_millis(250));
         println!(
             "{} {}",
             LogGenerator::timestamp().dimmed(),
-            format!("  Capacity: {} GB", capacity_gb).dimmed()
+            format!("  Capacity: {} GB", disk.capacity_gb).dimmed()
         );
         thread::sleep(Duration::from_millis(200));
         println!(
             "{} {}",
             LogGenerator::timestamp().dimmed(),
-            format!("  Block size: 512 bytes").dimmed()
+            "  Block size: 512 bytes".dimmed()
         );
         thread::sleep(Duration::from_millis(300));
 
@@ -115,11 +116,7 @@ impl InstallationStage for BootloaderStage {
         );
         thread::sleep(Duration::from_millis(250));
 
-        let boot_partition = if device == "/dev/nvme0n1" {
-            format!("{}p2", device)
-        } else {
-            format!("{}2", device)
-        };
+        let boot_partition = disk.partition(2);
 
         println!(
             "{} {}",
@@ -129,11 +126,7 @@ impl InstallationStage for BootloaderStage {
         thread::sleep(Duration::from_millis(250));
 
         if is_efi {
-            let efi_partition = if device == "/dev/nvme0n1" {
-                format!("{}p1", device)
-            } else {
-                format!("{}1", device)
-            };
+            let efi_partition = disk.partition(1);
             println!(
                 "{} {}",
                 LogGenerator::timestamp().dimmed(),
@@ -331,13 +324,7 @@ impl InstallationStage for BootloaderStage {
                 format!("Found linux image: /boot/{}", kernel).dimmed()
             );
 
-            let initrd_suffix = if kernel.contains("generic") {
-                &kernel[8..]
-            } else if kernel.contains("amd64") {
-                &kernel[8..]
-            } else {
-                &kernel[8..]
-            };
+            let initrd_suffix = &kernel[8..];
 
             println!(
                 "{} {}",
@@ -357,11 +344,7 @@ impl InstallationStage for BootloaderStage {
         thread::sleep(Duration::from_millis(300));
 
         if rng.gen_bool(self.config.windows_found_chance) {
-            let partition = if device == "/dev/nvme0n1" {
-                format!("{}p3", device)
-            } else {
-                format!("{}3", device)
-            };
+            let partition = disk.partition(3);
             println!(
                 "{} {}",
                 LogGenerator::timestamp().dimmed(),
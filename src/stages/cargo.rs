@@ -0,0 +1,520 @@
+use super::InstallationStage;
+use crate::log_generator::LogGenerator;
+use crate::ui::prompt_retry;
+use colored::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// (name, version, download size in KB, dependencies)
+const CRATES: &[(&str, &str, u32, &[&str])] = &[
+    ("unicode-ident", "1.0.12", 42, &[]),
+    ("proc-macro2", "1.0.86", 48, &["unicode-ident"]),
+    ("quote", "1.0.36", 28, &["proc-macro2"]),
+    (
+        "syn",
+        "2.0.72",
+        262,
+        &["proc-macro2", "quote", "unicode-ident"],
+    ),
+    (
+        "serde_derive",
+        "1.0.204",
+        55,
+        &["proc-macro2", "quote", "syn"],
+    ),
+    ("serde", "1.0.204", 77, &["serde_derive"]),
+    ("itoa", "1.0.11", 10, &[]),
+    ("ryu", "1.0.18", 47, &[]),
+    ("memchr", "2.7.4", 94, &[]),
+    (
+        "serde_json",
+        "1.0.120",
+        146,
+        &["serde", "itoa", "ryu", "memchr"],
+    ),
+    ("libc", "0.2.155", 743, &[]),
+    ("cfg-if", "1.0.0", 7, &[]),
+    ("log", "0.4.22", 44, &[]),
+    ("bytes", "1.6.1", 59, &[]),
+    ("pin-project-lite", "0.2.14", 28, &[]),
+    ("mio", "1.0.1", 100, &["libc", "log"]),
+    ("socket2", "0.5.7", 55, &["libc"]),
+    (
+        "tokio-macros",
+        "2.3.0",
+        12,
+        &["proc-macro2", "quote", "syn"],
+    ),
+    (
+        "tokio",
+        "1.39.2",
+        760,
+        &[
+            "bytes",
+            "libc",
+            "mio",
+            "pin-project-lite",
+            "socket2",
+            "tokio-macros",
+        ],
+    ),
+    ("regex-syntax", "0.8.4", 340, &[]),
+    ("aho-corasick", "1.1.3", 180, &["memchr"]),
+    (
+        "regex-automata",
+        "0.4.7",
+        600,
+        &["aho-corasick", "memchr", "regex-syntax"],
+    ),
+    ("regex", "1.10.5", 250, &["regex-automata", "regex-syntax"]),
+    ("anstyle", "1.0.7", 15, &[]),
+    ("clap_lex", "0.7.1", 12, &[]),
+    ("strsim", "0.11.1", 14, &[]),
+    (
+        "clap_builder",
+        "4.5.9",
+        160,
+        &["anstyle", "clap_lex", "strsim"],
+    ),
+    ("clap_derive", "4.5.8", 30, &["proc-macro2", "quote", "syn"]),
+    ("clap", "4.5.9", 54, &["clap_builder", "clap_derive"]),
+    ("anyhow", "1.0.86", 45, &[]),
+    (
+        "thiserror-impl",
+        "1.0.63",
+        15,
+        &["proc-macro2", "quote", "syn"],
+    ),
+    ("thiserror", "1.0.63", 22, &["thiserror-impl"]),
+    ("tracing-core", "0.1.32", 60, &[]),
+    (
+        "tracing-attributes",
+        "0.1.27",
+        30,
+        &["proc-macro2", "quote", "syn"],
+    ),
+    (
+        "tracing",
+        "0.1.40",
+        180,
+        &[
+            "log",
+            "pin-project-lite",
+            "tracing-attributes",
+            "tracing-core",
+        ],
+    ),
+    ("rand_core", "0.6.4", 22, &["getrandom"]),
+    ("getrandom", "0.2.15", 37, &["cfg-if", "libc"]),
+    ("rand", "0.8.5", 87, &["libc", "rand_core"]),
+];
+
+/// Crates the workspace depends on directly
+const ROOTS: &[&str] = &[
+    "serde",
+    "serde_json",
+    "tokio",
+    "clap",
+    "anyhow",
+    "thiserror",
+    "tracing",
+    "regex",
+    "rand",
+];
+
+const WORKSPACE_MEMBERS: &[&str] = &["installer-core", "installer-cli"];
+
+/// (code, message, span lines as (line number, source, marker, label))
+type Diagnostic = (
+    &'static str,
+    &'static str,
+    &'static [(u32, &'static str, &'static str, &'static str)],
+);
+
+const WARNINGS: &[Diagnostic] = &[
+    (
+        "unused_variables",
+        "unused variable: `config`",
+        &[(
+            42,
+            "    let config = Config::load()?;",
+            "        ^^^^^^",
+            "help: if this is intentional, prefix it with an underscore: `_config`",
+        )],
+    ),
+    (
+        "dead_code",
+        "function `legacy_install` is never used",
+        &[(
+            118,
+            "fn legacy_install(stage: &Stage) -> Result<()> {",
+            "   ^^^^^^^^^^^^^^",
+            "",
+        )],
+    ),
+    (
+        "unused_imports",
+        "unused import: `std::collections::HashMap`",
+        &[(
+            3,
+            "use std::collections::HashMap;",
+            "    ^^^^^^^^^^^^^^^^^^^^^^^^^",
+            "",
+        )],
+    ),
+];
+
+const BORROW_ERRORS: &[Diagnostic] = &[
+    (
+        "E0502",
+        "cannot borrow `stages` as mutable because it is also borrowed as immutable",
+        &[
+            (
+                85,
+                "    let first = stages.first();",
+                "                ------",
+                "immutable borrow occurs here",
+            ),
+            (
+                87,
+                "    stages.push(Stage::default());",
+                "    ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^",
+                "mutable borrow occurs here",
+            ),
+            (
+                88,
+                "    first.map(|s| s.run());",
+                "    -----",
+                "immutable borrow later used here",
+            ),
+        ],
+    ),
+    (
+        "E0382",
+        "borrow of moved value: `progress`",
+        &[
+            (
+                131,
+                "    let handle = thread::spawn(move || progress.tick());",
+                "                               -------",
+                "value moved into closure here",
+            ),
+            (
+                133,
+                "    progress.finish();",
+                "    ^^^^^^^^",
+                "value borrowed here after move",
+            ),
+        ],
+    ),
+    (
+        "E0597",
+        "`buffer` does not live long enough",
+        &[
+            (
+                204,
+                "        let buffer = String::from_utf8_lossy(&bytes);",
+                "            ------",
+                "binding `buffer` declared here",
+            ),
+            (
+                205,
+                "        line = buffer.trim();",
+                "               ^^^^^^",
+                "borrowed value does not live long enough",
+            ),
+            (
+                206,
+                "    }",
+                "    -",
+                "`buffer` dropped here while still borrowed",
+            ),
+        ],
+    ),
+];
+
+pub struct CargoStage;
+
+impl CargoStage {
+    /// Pick some direct dependencies and resolve them, dependencies first.
+    fn resolve(rng: &mut rand::rngs::ThreadRng) -> Vec<(&'static str, &'static str, u32)> {
+        fn visit(name: &str, order: &mut Vec<(&'static str, &'static str, u32)>) {
+            if order.iter().any(|(n, _, _)| *n == name) {
+                return;
+            }
+            if let Some((crate_name, version, size, deps)) =
+                CRATES.iter().find(|(n, _, _, _)| *n == name)
+            {
+                for dep in *deps {
+                    visit(dep, order);
+                }
+                order.push((crate_name, version, *size));
+            }
+        }
+
+        let count = rng.gen_range(4..=ROOTS.len());
+        let mut order = Vec::new();
+        for root in ROOTS.choose_multiple(rng, count) {
+            visit(root, &mut order);
+        }
+        order
+    }
+
+    /// Cargo's right-aligned green status word.
+    fn status(word: &str, rest: &str) {
+        println!("{} {}", format!("{:>12}", word).bright_green().bold(), rest);
+    }
+
+    /// The `Building [===>   ] 12/57: a, b, c` line cargo keeps at the bottom.
+    fn building_line(done: usize, total: usize, active: &[&str]) -> String {
+        let width = 25;
+        let filled = done * width / total.max(1);
+        let bar = if filled >= width {
+            "=".repeat(width)
+        } else {
+            format!("{}>{}", "=".repeat(filled), " ".repeat(width - filled - 1))
+        };
+        format!(
+            "{} [{}] {}/{}: {}",
+            format!("{:>12}", "Building").bright_cyan().bold(),
+            bar,
+            done,
+            total,
+            active.join(", ")
+        )
+    }
+
+    fn print_diagnostic(level: ColoredString, diagnostic: &Diagnostic, file: &str) {
+        let (code, message, spans) = diagnostic;
+        let (first_line, ..) = spans[0];
+        let gutter = " ".repeat(spans.last().map(|s| s.0).unwrap_or(0).to_string().len());
+
+        if code.starts_with('E') {
+            println!(
+                "{}{}: {}",
+                level,
+                format!("[{}]", code).bright_red().bold(),
+                message.bold()
+            );
+        } else {
+            println!("{}: {}", level, message.bold());
+        }
+        println!(
+            "{}{} {}:{}:{}",
+            gutter,
+            "-->".bright_blue().bold(),
+            file,
+            first_line,
+            rand::thread_rng().gen_range(5..13)
+        );
+        println!("{} {}", gutter, "|".bright_blue().bold());
+        for (line, source, marker, label) in *spans {
+            println!(
+                "{} {} {}",
+                format!("{:>width$}", line, width = gutter.len())
+                    .bright_blue()
+                    .bold(),
+                "|".bright_blue().bold(),
+                source
+            );
+            let marker = if marker.contains('^') {
+                marker.bright_red().bold()
+            } else {
+                marker.bright_blue().bold()
+            };
+            println!(
+                "{} {} {} {}",
+                gutter,
+                "|".bright_blue().bold(),
+                marker,
+                label
+            );
+        }
+        println!("{} {}", gutter, "|".bright_blue().bold());
+        if !code.starts_with('E') {
+            println!(
+                "{} {} {}: `#[warn({})]` on by default",
+                gutter,
+                "=".bright_blue().bold(),
+                "note".bold(),
+                code
+            );
+        }
+        println!();
+    }
+
+    fn compile(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        crates: &[(String, String)],
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let jobs = rng.gen_range(2..=4);
+        for (i, (name, version)) in crates.iter().enumerate() {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+
+            print!("\r{:100}\r", "");
+            Self::status("Compiling", &format!("{} v{}", name, version));
+
+            let active: Vec<&str> = crates[i..]
+                .iter()
+                .take(jobs)
+                .map(|(name, _)| name.as_str())
+                .collect();
+            print!("{}", Self::building_line(i, crates.len(), &active));
+            io::stdout().flush()?;
+
+            // A few heavyweights dominate any real build
+            let delay = if ["syn", "tokio", "regex-automata", "libc", "clap_builder"]
+                .contains(&name.as_str())
+            {
+                rng.gen_range(600..1400)
+            } else {
+                rng.gen_range(40..250)
+            };
+            thread::sleep(Duration::from_millis(delay));
+        }
+        print!("\r{:100}\r", "");
+        io::stdout().flush()?;
+        Ok(())
+    }
+}
+
+impl InstallationStage for CargoStage {
+    fn name(&self) -> &'static str {
+        "Rust Workspace Build"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let graph = Self::resolve(&mut rng);
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "$ cargo build --release".bright_white()
+        );
+        Self::status("Updating", "crates.io index");
+        thread::sleep(Duration::from_millis(rng.gen_range(800..1800)));
+        Self::status(
+            "Locking",
+            &format!("{} packages to latest compatible versions", graph.len()),
+        );
+        thread::sleep(Duration::from_millis(300));
+
+        Self::status("Downloading", "crates ...");
+        let mut total_kb = 0;
+        for (name, version, size) in &graph {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            total_kb += size;
+            Self::status(
+                "Downloaded",
+                &format!(
+                    "{} v{} {}",
+                    name,
+                    version,
+                    format!("({} KB)", size).dimmed()
+                ),
+            );
+            thread::sleep(Duration::from_millis(
+                rng.gen_range(20..20 + *size as u64 / 4),
+            ));
+        }
+        Self::status(
+            "Downloaded",
+            &format!(
+                "{} crates ({:.1} MB) in {:.2}s",
+                graph.len(),
+                total_kb as f64 / 1000.0,
+                rng.gen_range(0.8..4.0)
+            ),
+        );
+
+        let deps: Vec<(String, String)> = graph
+            .iter()
+            .map(|(name, version, _)| (name.to_string(), version.to_string()))
+            .collect();
+        let members: Vec<(String, String)> = WORKSPACE_MEMBERS
+            .iter()
+            .map(|member| (member.to_string(), "0.1.0".to_string()))
+            .collect();
+
+        self.compile(&mut rng, &deps, exit_check)?;
+
+        let member_file = |member: &str| format!("{}/src/lib.rs", member);
+
+        // The first member's diagnostics follow its own Compiling line, and
+        // a retry after an error picks up from there without a new one
+        self.compile(&mut rng, &members[..1], exit_check)?;
+
+        if rng.gen_bool(0.5) {
+            let warning = WARNINGS.choose(&mut rng).unwrap();
+            Self::print_diagnostic(
+                "warning".bright_yellow().bold(),
+                warning,
+                &member_file(WORKSPACE_MEMBERS[0]),
+            );
+            println!(
+                "{}: `{}` (lib) generated 1 warning",
+                "warning".bright_yellow().bold(),
+                WORKSPACE_MEMBERS[0]
+            );
+        }
+
+        if rng.gen_bool(0.25) {
+            let error = BORROW_ERRORS.choose(&mut rng).unwrap();
+            Self::print_diagnostic(
+                "error".bright_red().bold(),
+                error,
+                &member_file(WORKSPACE_MEMBERS[0]),
+            );
+            println!(
+                "For more information about this error, try `rustc --explain {}`.",
+                error.0
+            );
+            println!(
+                "{}: could not compile `{}` (lib) due to 1 previous error",
+                "error".bright_red().bold(),
+                WORKSPACE_MEMBERS[0]
+            );
+
+            if !prompt_retry("Applying suggested fix and rebuilding...")? {
+                println!(
+                    "{} {}",
+                    LogGenerator::timestamp().dimmed(),
+                    "Skipping workspace build...".dimmed()
+                );
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(rng.gen_range(800..1500)));
+        }
+
+        self.compile(&mut rng, &members[1..], exit_check)?;
+
+        let secs: u32 = rng.gen_range(20..240);
+        Self::status(
+            "Finished",
+            &format!(
+                "`release` profile [optimized] target(s) in {}",
+                if secs >= 60 {
+                    format!("{}m {:02}s", secs / 60, secs % 60)
+                } else {
+                    format!("{}.{:02}s", secs, rng.gen_range(0..100))
+                }
+            ),
+        );
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,376 @@
+use super::InstallationStage;
+use crate::host_profile::HostProfile;
+use crate::log_generator::LogGenerator;
+use crate::ui::Spinner;
+use chrono::{Duration as ChronoDuration, Utc};
+use colored::*;
+use rand::Rng;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const DIRECTORY: &str = "https://acme-v02.api.letsencrypt.org";
+
+/// (subject, issuer) for the intermediate and root above the leaf
+const CHAIN: &[(&str, &str)] = &[
+    (
+        "C = US, O = Let's Encrypt, CN = R11",
+        "C = US, O = Internet Security Research Group, CN = ISRG Root X1",
+    ),
+    (
+        "C = US, O = Internet Security Research Group, CN = ISRG Root X1",
+        "O = Digital Signature Trust Co., CN = DST Root CA X3",
+    ),
+];
+
+pub struct CertificatesStage {
+    host: Arc<HostProfile>,
+}
+
+impl CertificatesStage {
+    pub fn new(host: Arc<HostProfile>) -> Self {
+        Self { host }
+    }
+
+    /// The hostname as a DNS label under a domain we pretend to own.
+    fn domain(&self) -> String {
+        let label: String = self
+            .host
+            .hostname
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let label = label.trim_matches('-');
+        if label.is_empty() {
+            "example.com".to_string()
+        } else {
+            format!("{}.example.com", label)
+        }
+    }
+
+    /// A base64url-looking token, as used for challenge tokens and TXT values.
+    fn token(rng: &mut rand::rngs::ThreadRng, len: usize) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+        (0..len)
+            .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+            .collect()
+    }
+
+    /// A certificate serial in openssl's colon-separated form.
+    fn serial() -> String {
+        let hex = format!(
+            "{}{}{}",
+            &LogGenerator::hex_addr()[2..],
+            &LogGenerator::hex_addr()[2..],
+            &LogGenerator::hex_addr()[2..]
+        )
+        .to_uppercase();
+        // Serials are 18 bytes; a leading nibble below 8 keeps them positive
+        let hex = format!("0{}", &hex[..35]);
+        hex.as_bytes()
+            .chunks(2)
+            .map(|pair| String::from_utf8_lossy(pair).to_string())
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+
+    fn request(method: &str, path: &str, status: &str) {
+        println!(
+            "{} {} {}{} {}",
+            LogGenerator::timestamp().dimmed(),
+            method.bright_cyan(),
+            DIRECTORY.dimmed(),
+            path,
+            status.bright_green()
+        );
+    }
+
+    /// The classic `genrsa` progress: a dot per candidate tested, a plus
+    /// per Miller-Rabin round once a prime turns up.
+    fn generate_key(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        path: &str,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("$ openssl genrsa -out {} 4096", path).bright_white()
+        );
+        println!("Generating RSA private key, 4096 bit long modulus (2 primes)");
+        for _ in 0..2 {
+            let dots = rng.gen_range(5..60);
+            for _ in 0..dots {
+                if exit_check() {
+                    println!();
+                    return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+                }
+                print!(".");
+                io::stdout().flush()?;
+                thread::sleep(Duration::from_millis(rng.gen_range(10..80)));
+            }
+            for _ in 0..5 {
+                print!("{}", "+".bright_green());
+                io::stdout().flush()?;
+                thread::sleep(Duration::from_millis(rng.gen_range(40..120)));
+            }
+            println!();
+        }
+        println!("e is 65537 (0x010001)");
+        println!();
+        Ok(())
+    }
+
+    fn http_challenge(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        spinner: &mut Spinner,
+        domain: &str,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let token = Self::token(rng, 43);
+        println!(
+            "{} {}",
+            "http-01".bright_magenta(),
+            format!("challenge for {}", domain).bright_white()
+        );
+        println!(
+            "  Serving http://{}/.well-known/acme-challenge/{}",
+            domain,
+            token.dimmed()
+        );
+        Self::request(
+            "POST",
+            &format!(
+                "/acme/chall/{}/{}",
+                rng.gen_range(100_000_000..999_999_999),
+                &token[..6]
+            ),
+            "200 OK",
+        );
+        spinner.animate("Waiting for verification...", 2000, exit_check)?;
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!(
+                "GET /.well-known/acme-challenge/{} 200 \"Let's Encrypt validation server\"",
+                token
+            )
+            .dimmed()
+        );
+        println!(
+            "  {} {}",
+            "✓".bright_green(),
+            format!("{} validated", domain).bright_green()
+        );
+        println!();
+        Ok(())
+    }
+
+    fn dns_challenge(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        spinner: &mut Spinner,
+        domain: &str,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let base = domain.trim_start_matches("*.");
+        let record = format!("_acme-challenge.{}", base);
+        let value = Self::token(rng, 43);
+        println!(
+            "{} {}",
+            "dns-01".bright_magenta(),
+            format!("challenge for {}", domain).bright_white()
+        );
+        println!("  Please deploy a DNS TXT record under the name:");
+        println!("  {}", record.bright_white());
+        println!("  with the following value:");
+        println!("  {}", value.bright_yellow());
+        println!();
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("dns-api: added TXT {} \"{}\" ttl=60", record, value).dimmed()
+        );
+
+        // Some nameservers always take longer than the TTL promises
+        let lagging = rng.gen_range(0..3);
+        for round in 0..=lagging {
+            spinner.animate(
+                &format!("Waiting for DNS propagation ({}s)...", 30 * (round + 1)),
+                rng.gen_range(1500..3000),
+                exit_check,
+            )?;
+            for ns in ["ns1", "ns2", "ns3"] {
+                let visible = round == lagging || (ns == "ns1" && round > 0);
+                println!(
+                    "  dig +short TXT {} @{}.example.com  {}",
+                    record,
+                    ns,
+                    if visible {
+                        format!("\"{}\"", value).bright_green()
+                    } else {
+                        "(no answer)".yellow()
+                    }
+                );
+            }
+        }
+
+        Self::request(
+            "POST",
+            &format!(
+                "/acme/chall/{}/{}",
+                rng.gen_range(100_000_000..999_999_999),
+                &value[..6]
+            ),
+            "200 OK",
+        );
+        spinner.animate("Waiting for verification...", 2500, exit_check)?;
+        println!(
+            "  {} {}",
+            "✓".bright_green(),
+            format!("{} validated", domain).bright_green()
+        );
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("dns-api: removed TXT {}", record).dimmed()
+        );
+        println!();
+        Ok(())
+    }
+
+    fn print_chain(&self, domain: &str) {
+        let now = Utc::now();
+        let format = "%b %e %H:%M:%S %Y GMT";
+        println!("{}", "Certificate chain".bright_white());
+        println!(" 0 s:CN = {}", domain.bright_white());
+        println!("   i:{}", CHAIN[0].0);
+        println!("   Serial: {}", Self::serial().bright_cyan());
+        println!(
+            "   v:NotBefore: {}; NotAfter: {}",
+            now.format(format),
+            (now + ChronoDuration::days(90)).format(format)
+        );
+        for (i, (subject, issuer)) in CHAIN.iter().enumerate() {
+            println!(" {} s:{}", i + 1, subject);
+            println!("   i:{}", issuer);
+            println!("   Serial: {}", Self::serial().bright_cyan());
+        }
+    }
+}
+
+impl InstallationStage for CertificatesStage {
+    fn name(&self) -> &'static str {
+        "TLS Certificate Issuance"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+        let domain = self.domain();
+        let wildcard = format!("*.{}", domain);
+        let key_path = format!("/etc/ssl/private/{}.key", domain);
+
+        self.generate_key(&mut rng, &key_path, exit_check)?;
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!(
+                "$ openssl req -new -key {} -subj \"/CN={}\" -addext \"subjectAltName=DNS:{},DNS:{}\" -out {}.csr",
+                key_path, domain, domain, wildcard, domain
+            )
+            .bright_white()
+        );
+        thread::sleep(Duration::from_millis(500));
+        println!();
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!(
+                "$ acme-client --server {}/directory issue --csr {}.csr",
+                DIRECTORY, domain
+            )
+            .bright_white()
+        );
+        Self::request("GET", "/directory", "200 OK");
+        Self::request("HEAD", "/acme/new-nonce", "200 OK");
+        spinner.animate("Registering account...", 1200, exit_check)?;
+        let account = rng.gen_range(100_000_000..2_000_000_000u64);
+        Self::request("POST", "/acme/new-acct", "201 Created");
+        println!(
+            "  Account registered: {}/acme/acct/{}",
+            DIRECTORY,
+            account.to_string().bright_white()
+        );
+        println!("  Terms of service accepted: https://letsencrypt.org/documents/LE-SA-v1.4.pdf");
+        println!();
+
+        Self::request("POST", "/acme/new-order", "201 Created");
+        let order = rng.gen_range(1_000_000_000..9_999_999_999u64);
+        println!(
+            "  Order {} for {}, {}",
+            order.to_string().bright_white(),
+            domain,
+            wildcard
+        );
+        println!();
+
+        self.http_challenge(&mut rng, &mut spinner, &domain, exit_check)?;
+        self.dns_challenge(&mut rng, &mut spinner, &wildcard, exit_check)?;
+
+        Self::request(
+            "POST",
+            &format!("/acme/finalize/{}/{}", account, order),
+            "200 OK",
+        );
+        println!("  Order status: {}", "processing".yellow());
+        spinner.animate("Waiting for issuance...", 2000, exit_check)?;
+        println!("  Order status: {}", "valid".bright_green());
+        Self::request(
+            "POST",
+            &format!(
+                "/acme/cert/{}",
+                &Self::serial().replace(':', "").to_lowercase()
+            ),
+            "200 OK",
+        );
+        println!();
+
+        self.print_chain(&domain);
+        println!();
+        println!(
+            "{} {}",
+            "Successfully received certificate.".bright_green(),
+            format!("Saved to /etc/ssl/certs/{}/fullchain.pem", domain).dimmed()
+        );
+        println!("This certificate expires in 90 days. Renewal is scheduled by acme-client.timer.");
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serial_is_eighteen_positive_bytes() {
+        let serial = CertificatesStage::serial();
+        let bytes: Vec<&str> = serial.split(':').collect();
+        assert_eq!(bytes.len(), 18);
+        assert!(bytes
+            .iter()
+            .all(|b| b.len() == 2 && b.chars().all(|c| c.is_ascii_hexdigit())));
+        assert!(bytes[0].starts_with('0'));
+    }
+}
@@ -4,10 +4,54 @@ use crate::ui::{ProgressBar, ProgressStyle, Spinner};
 use colored::*;
 use rand::Rng;
 use std::io;
+use std::sync::{Arc, OnceLock};
 use std::thread;
 use std::time::Duration;
 
-pub struct DatabaseStage;
+/// The database server a cycle installs. Stages that talk to the database
+/// share one cell so they all agree on which server it is.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DatabaseEngine {
+    MySql,
+    PostgreSql,
+}
+
+impl DatabaseEngine {
+    /// The engine already chosen this cycle, or a fresh coin flip.
+    pub fn choose(cell: &OnceLock<DatabaseEngine>) -> Self {
+        *cell.get_or_init(|| {
+            if rand::thread_rng().gen_bool(0.5) {
+                DatabaseEngine::MySql
+            } else {
+                DatabaseEngine::PostgreSql
+            }
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DatabaseEngine::MySql => "MySQL",
+            DatabaseEngine::PostgreSql => "PostgreSQL",
+        }
+    }
+
+    pub fn version(&self) -> &'static str {
+        match self {
+            DatabaseEngine::MySql => "8.0.28",
+            DatabaseEngine::PostgreSql => "14.2",
+        }
+    }
+}
+
+pub struct DatabaseStage {
+    engine: Arc<OnceLock<DatabaseEngine>>,
+}
+
+impl DatabaseStage {
+    pub fn new(engine: Arc<OnceLock<DatabaseEngine>>) -> Self {
+        Self { engine }
+    }
+}
 
 impl InstallationStage for DatabaseStage {
     fn name(&self) -> &'static str {
@@ -21,12 +65,9 @@ impl InstallationStage for DatabaseStage {
         let mut rng = rand::thread_rng();
         let mut spinner = Spinner::new();
 
-        let db_type = if rng.gen_bool(0.5) {
-            "MySQL"
-        } else {
-            "PostgreSQL"
-        };
-        let version = if db_type == "MySQL" { "8.0.28" } else { "14.2" };
+        let engine = DatabaseEngine::choose(&self.engine);
+        let db_type = engine.name();
+        let version = engine.version();
 
         println!(
             "{} {}",
@@ -37,7 +78,7 @@ impl InstallationStage for DatabaseStage {
 
         spinner.animate("Initializing database cluster...", 2000, exit_check)?;
 
-        if db_type == "PostgreSQL" {
+        if engine == DatabaseEngine::PostgreSql {
             println!(
                 "{} {}",
                 LogGenerator::timestamp().dimmed(),
@@ -100,7 +141,7 @@ impl InstallationStage for DatabaseStage {
             LogGenerator::timestamp().dimmed(),
             format!(
                 "    {} -D /var/lib/{}/data",
-                if db_type == "PostgreSQL" {
+                if engine == DatabaseEngine::PostgreSql {
                     "pg_ctl"
                 } else {
                     "mysqld"
@@ -1,11 +1,10 @@
 use super::InstallationStage;
 use crate::deno_logs::DenoLogs;
 use crate::log_generator::LogGenerator;
-use crate::ui::{ProgressBar, ProgressStyle};
+use crate::ui::{prompt_retry, ProgressBar, ProgressStyle};
 use colored::*;
-use crossterm::event::{self, Event, KeyCode};
 use rand::Rng;
-use std::io::{self, Write};
+use std::io;
 use std::thread;
 use std::time::Duration;
 
@@ -71,43 +70,6 @@ impl DenoStage {
 
         Ok(())
     }
-
-    /// Prompt user to retry or abort
-    fn prompt_retry(&self) -> io::Result<bool> {
-        println!();
-        print!("{}", "Try again or abort? [1-2]: ".bright_yellow().bold());
-        io::stdout().flush()?;
-
-        loop {
-            if let Ok(Event::Key(key_event)) = event::read() {
-                match key_event.code {
-                    KeyCode::Char('1') => {
-                        println!("1");
-                        println!(
-                            "{} {}",
-                            LogGenerator::timestamp().dimmed(),
-                            "Retrying compilation...".bright_cyan()
-                        );
-                        thread::sleep(Duration::from_millis(1000));
-                        return Ok(true);
-                    }
-                    KeyCode::Char('2') => {
-                        println!("2");
-                        println!(
-                            "{} {}",
-                            LogGenerator::timestamp().dimmed(),
-                            "Aborting...".bright_red()
-                        );
-                        thread::sleep(Duration::from_millis(500));
-                        return Ok(false);
-                    }
-                    _ => {
-                        continue;
-                    }
-                }
-            }
-        }
-    }
 }
 
 impl InstallationStage for DenoStage {
@@ -140,7 +102,7 @@ impl InstallationStage for DenoStage {
                 "Build failed! The installation encountered errors.".bright_red()
             );
 
-            let retry = self.prompt_retry()?;
+            let retry = prompt_retry("Retrying compilation...")?;
 
             if retry {
                 println!();
@@ -0,0 +1,518 @@
+use super::InstallationStage;
+use crate::host_profile::HostProfile;
+use crate::log_generator::LogGenerator;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use colored::*;
+use crossterm::{
+    execute,
+    terminal::{Clear, ClearType},
+};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// The packages one apt-based release upgrades, removes and holds back.
+struct AptRelease {
+    mirror: &'static str,
+    /// (package, installed version, candidate version)
+    upgrades: &'static [(&'static str, &'static str, &'static str)],
+    /// New kernel packages, all fetched at `kernel_version`
+    new: &'static [&'static str],
+    kernel_version: &'static str,
+    /// The initrd `update-initramfs` builds for the new kernel
+    initrd: &'static str,
+    removed: &'static [&'static str],
+    /// Phased or dependency-blocked updates apt won't touch in a full-upgrade
+    held: &'static [&'static str],
+    /// (trigger package, version)
+    triggers: &'static [(&'static str, &'static str)],
+}
+
+const UBUNTU: AptRelease = AptRelease {
+    mirror: "http://archive.ubuntu.com/ubuntu noble-updates",
+    upgrades: &[
+        ("libc6", "2.39-0ubuntu8.2", "2.39-0ubuntu8.3"),
+        ("libc-bin", "2.39-0ubuntu8.2", "2.39-0ubuntu8.3"),
+        (
+            "openssh-server",
+            "1:9.6p1-3ubuntu13.4",
+            "1:9.6p1-3ubuntu13.5",
+        ),
+        (
+            "openssh-client",
+            "1:9.6p1-3ubuntu13.4",
+            "1:9.6p1-3ubuntu13.5",
+        ),
+        ("systemd", "255.4-1ubuntu8.2", "255.4-1ubuntu8.4"),
+        ("libssl3t64", "3.0.13-0ubuntu3.1", "3.0.13-0ubuntu3.4"),
+        (
+            "linux-firmware",
+            "20240318.git3b128b60-0ubuntu2.1",
+            "20240318.git3b128b60-0ubuntu2.3",
+        ),
+        ("python3.12", "3.12.3-1ubuntu0.1", "3.12.3-1ubuntu0.2"),
+        ("curl", "8.5.0-2ubuntu10.1", "8.5.0-2ubuntu10.4"),
+        ("git", "1:2.43.0-1ubuntu7.1", "1:2.43.0-1ubuntu7.2"),
+        ("vim", "2:9.1.0016-1ubuntu7.2", "2:9.1.0016-1ubuntu7.3"),
+        ("tzdata", "2024a-3ubuntu1.1", "2024b-0ubuntu0.24.04"),
+    ],
+    new: &[
+        "linux-image-6.8.0-45-generic",
+        "linux-modules-6.8.0-45-generic",
+        "linux-headers-6.8.0-45",
+    ],
+    kernel_version: "6.8.0-45.45",
+    initrd: "/boot/initrd.img-6.8.0-45-generic",
+    removed: &[
+        "linux-image-6.8.0-31-generic",
+        "linux-modules-6.8.0-31-generic",
+        "python3-distutils",
+        "libpython3.11-minimal",
+    ],
+    held: &["ubuntu-drivers-common", "gnome-shell", "mutter-common"],
+    triggers: &[
+        ("man-db", "2.12.0-4build2"),
+        ("libc-bin", "2.39-0ubuntu8.3"),
+        ("dbus", "1.14.10-4ubuntu4.1"),
+        ("hicolor-icon-theme", "0.17-2"),
+        ("initramfs-tools", "0.142ubuntu25.2"),
+    ],
+};
+
+const DEBIAN: AptRelease = AptRelease {
+    mirror: "http://deb.debian.org/debian bookworm",
+    upgrades: &[
+        ("libc6", "2.36-9+deb12u7", "2.36-9+deb12u8"),
+        ("libc-bin", "2.36-9+deb12u7", "2.36-9+deb12u8"),
+        ("openssh-server", "1:9.2p1-2+deb12u2", "1:9.2p1-2+deb12u3"),
+        ("openssh-client", "1:9.2p1-2+deb12u2", "1:9.2p1-2+deb12u3"),
+        ("systemd", "252.26-1~deb12u2", "252.30-1~deb12u2"),
+        ("libssl3", "3.0.13-1~deb12u1", "3.0.14-1~deb12u2"),
+        ("python3.11", "3.11.2-6", "3.11.2-6+deb12u3"),
+        ("curl", "7.88.1-10+deb12u7", "7.88.1-10+deb12u8"),
+        ("git", "1:2.39.2-1.1", "1:2.39.5-0+deb12u1"),
+        ("vim", "2:9.0.1378-2", "2:9.0.1378-2+deb12u1"),
+        ("tzdata", "2024a-0+deb12u1", "2024b-0+deb12u1"),
+    ],
+    new: &[
+        "linux-image-6.1.0-26-amd64",
+        "linux-headers-6.1.0-26-amd64",
+        "linux-headers-6.1.0-26-common",
+    ],
+    kernel_version: "6.1.112-1",
+    initrd: "/boot/initrd.img-6.1.0-26-amd64",
+    removed: &[
+        "linux-image-6.1.0-21-amd64",
+        "linux-headers-6.1.0-21-amd64",
+        "linux-headers-6.1.0-21-common",
+    ],
+    held: &["firefox-esr", "libreoffice-core", "nvidia-kernel-dkms"],
+    triggers: &[
+        ("man-db", "2.11.2-2"),
+        ("libc-bin", "2.36-9+deb12u8"),
+        ("dbus", "1.14.10-1~deb12u1"),
+        ("hicolor-icon-theme", "0.17-2"),
+        ("initramfs-tools", "0.142+deb12u1"),
+    ],
+};
+
+/// (package, arch, installed version, candidate version)
+const DNF_UPGRADES: &[(&str, &str, &str, &str)] = &[
+    ("glibc", "x86_64", "2.39-22.fc40", "2.40-3.fc41"),
+    ("openssh-server", "x86_64", "9.6p1-1.fc40.4", "9.8p1-3.fc41"),
+    ("systemd", "x86_64", "255.12-1.fc40", "256.7-1.fc41"),
+    ("kernel", "x86_64", "6.10.12-200.fc40", "6.11.4-301.fc41"),
+    ("openssl-libs", "x86_64", "1:3.2.2-3.fc40", "1:3.2.2-9.fc41"),
+    ("python3", "x86_64", "3.12.6-1.fc40", "3.13.0-1.fc41"),
+    ("dnf", "noarch", "4.21.1-1.fc40", "5:5.2.6.0-1.fc41"),
+    ("curl", "x86_64", "8.6.0-10.fc40", "8.9.1-2.fc41"),
+    ("git", "x86_64", "2.46.2-1.fc40", "2.47.0-1.fc41"),
+    ("gnome-shell", "x86_64", "46.5-1.fc40", "47.0-1.fc41"),
+];
+
+const DNF_REMOVED: &[&str] = &[
+    "python3-dnf-plugins-extras-common",
+    "libdnf",
+    "python3-hawkey",
+];
+
+/// Packages dnf skips because their new versions would conflict
+const DNF_SKIPPED: &[&str] = &["ffmpeg-libs", "akmod-nvidia", "VirtualBox-7.0"];
+
+/// Scriptlets rpm runs once the transaction is in place
+const DNF_SCRIPTLETS: &[&str] = &[
+    "glibc-common",
+    "systemd",
+    "kernel-core",
+    "shared-mime-info",
+    "fontconfig",
+];
+
+/// The package family to upgrade with, going by the detected OS.
+enum PackageManager {
+    Apt(&'static AptRelease),
+    Dnf,
+}
+
+impl PackageManager {
+    /// `None` for OSes neither family covers (macOS, Arch, openSUSE...).
+    fn detect(os_name: &str) -> Option<Self> {
+        let os = os_name.to_lowercase();
+        let any = |names: &[&str]| names.iter().any(|name| os.contains(name));
+        if any(&["debian"]) {
+            Some(Self::Apt(&DEBIAN))
+        } else if any(&["ubuntu", "mint", "pop!_os", "elementary", "zorin"]) {
+            Some(Self::Apt(&UBUNTU))
+        } else if any(&["fedora", "red hat", "rhel", "centos", "rocky", "alma"]) {
+            Some(Self::Dnf)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct DistUpgradeStage {
+    host: Arc<HostProfile>,
+}
+
+impl DistUpgradeStage {
+    pub fn new(host: Arc<HostProfile>) -> Self {
+        Self { host }
+    }
+
+    fn command(line: &str) {
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("$ {}", line).bright_white()
+        );
+    }
+
+    /// Print package names wrapped and indented the way apt lists them.
+    fn package_list(header: &str, packages: &[&str]) {
+        println!("{}", header);
+        let mut line = String::from(" ");
+        for package in packages {
+            if line.len() + package.len() + 1 > 78 {
+                println!("{}", line);
+                line = String::from(" ");
+            }
+            line.push(' ');
+            line.push_str(package);
+        }
+        println!("{}", line);
+    }
+
+    /// The dpkg prompt for a locally modified conffile, answered after a
+    /// pause with the default so the upgrade never blocks.
+    fn conffile_prompt(&self) -> io::Result<()> {
+        execute!(io::stdout(), Clear(ClearType::CurrentLine))?;
+        println!();
+        for line in [
+            "Configuration file '/etc/ssh/sshd_config'",
+            " ==> Modified (by you or by a script) since installation.",
+            " ==> Package distributor has shipped an updated version.",
+            "   What would you like to do about it ?  Your options are:",
+            "    Y or I  : install the package maintainer's version",
+            "    N or O  : keep your currently-installed version",
+            "      D     : show the differences between the versions",
+            "      Z     : start a shell to examine the situation",
+            " The default action is to keep your current version.",
+        ] {
+            println!("{}", line);
+        }
+        print!(
+            "{}",
+            "*** sshd_config (Y/I/N/O/D/Z) [default=N] ? "
+                .bright_white()
+                .bold()
+        );
+        io::stdout().flush()?;
+        thread::sleep(Duration::from_millis(2000));
+        println!("{}", "N".bright_white());
+        println!("Installing new version of config file /etc/ssh/sshd_config.dpkg-dist ...");
+        Ok(())
+    }
+
+    fn apt(
+        &self,
+        release: &AptRelease,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let mut spinner = Spinner::new();
+
+        Self::command("sudo apt full-upgrade");
+        spinner.animate("Reading package lists... Done", 900, exit_check)?;
+        spinner.animate("Building dependency tree... Done", 700, exit_check)?;
+        spinner.animate("Reading state information... Done", 400, exit_check)?;
+        spinner.animate("Calculating upgrade... Done", 1800, exit_check)?;
+
+        let count = rng.gen_range(1..=release.held.len());
+        let held: Vec<&str> = release.held.choose_multiple(rng, count).copied().collect();
+        Self::package_list("The following packages have been kept back:", &held);
+        Self::package_list(
+            &"The following packages will be REMOVED:"
+                .bright_red()
+                .to_string(),
+            release.removed,
+        );
+        Self::package_list("The following NEW packages will be installed:", release.new);
+        let upgrades: Vec<&str> = release.upgrades.iter().map(|(name, ..)| *name).collect();
+        Self::package_list("The following packages will be upgraded:", &upgrades);
+        println!(
+            "{} upgraded, {} newly installed, {} to remove and {} not upgraded.",
+            release.upgrades.len(),
+            release.new.len(),
+            release.removed.len(),
+            held.len()
+        );
+        println!(
+            "Need to get {:.1} MB of archives.",
+            rng.gen_range(180.0..420.0)
+        );
+        println!(
+            "After this operation, {:.1} MB of additional disk space will be used.",
+            rng.gen_range(20.0..160.0)
+        );
+        print!("Do you want to continue? [Y/n] ");
+        io::stdout().flush()?;
+        thread::sleep(Duration::from_millis(700));
+        println!("Y");
+
+        let fetches: Vec<(&str, &str)> = release
+            .new
+            .iter()
+            .map(|name| (*name, release.kernel_version))
+            .chain(release.upgrades.iter().map(|(name, _, new)| (*name, *new)))
+            .collect();
+        for (i, (name, version)) in fetches.iter().enumerate() {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            println!(
+                "Get:{} {}/main amd64 {} amd64 {} [{} kB]",
+                i + 1,
+                release.mirror,
+                name,
+                version,
+                rng.gen_range(40..60_000)
+            );
+            thread::sleep(Duration::from_millis(rng.gen_range(40..200)));
+        }
+        println!(
+            "Fetched {:.1} MB in {}s ({:.1} MB/s)",
+            rng.gen_range(180.0..420.0),
+            rng.gen_range(8..40),
+            rng.gen_range(8.0..40.0)
+        );
+
+        let progress = ProgressBar::new(ProgressStyle::Hash);
+        let total = release.removed.len() + fetches.len() * 2;
+        let mut done = 0;
+        let mut step = |message: String| -> io::Result<()> {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            done += 1;
+            let fraction = done as f32 / total as f32;
+            execute!(io::stdout(), Clear(ClearType::CurrentLine))?;
+            println!("{}", message);
+            print!(
+                "{} {}\r",
+                format!("Progress: [{:>3}%]", (fraction * 100.0) as u32)
+                    .black()
+                    .on_green(),
+                progress.render(fraction)
+            );
+            io::stdout().flush()?;
+            thread::sleep(Duration::from_millis(rng.gen_range(60..260)));
+            Ok(())
+        };
+
+        for name in release.removed {
+            step(format!("Removing {} ...", name))?;
+        }
+        for (name, old, new) in release.upgrades {
+            step(format!(
+                "Preparing to unpack .../{}_{}_amd64.deb ...\nUnpacking {} ({}) over ({}) ...",
+                name, new, name, new, old
+            ))?;
+        }
+        for name in release.new {
+            step(format!("Selecting previously unselected package {}.", name))?;
+        }
+        for (name, version) in &fetches {
+            step(format!("Setting up {} ({}) ...", name, version))?;
+            if *name == "openssh-server" {
+                self.conffile_prompt()?;
+            }
+        }
+        execute!(io::stdout(), Clear(ClearType::CurrentLine))?;
+
+        for (trigger, version) in release.triggers {
+            println!("Processing triggers for {} ({}) ...", trigger, version);
+            if *trigger == "initramfs-tools" {
+                spinner.animate(
+                    &format!("update-initramfs: Generating {}", release.initrd),
+                    1500,
+                    exit_check,
+                )?;
+            }
+            thread::sleep(Duration::from_millis(rng.gen_range(150..500)));
+        }
+        Ok(())
+    }
+
+    fn dnf(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let mut spinner = Spinner::new();
+
+        Self::command("sudo dnf system-upgrade download --releasever=41");
+        println!(
+            "Last metadata expiration check: 0:{:02}:{:02} ago.",
+            rng.gen_range(0..59),
+            rng.gen_range(0..59)
+        );
+        spinner.animate("Dependencies resolved.", 2000, exit_check)?;
+
+        println!("{}", "=".repeat(78));
+        println!(
+            " {:<24} {:<8} {:<22} {:<12} {:>6}",
+            "Package", "Arch", "Version", "Repository", "Size"
+        );
+        println!("{}", "=".repeat(78));
+        println!("Upgrading:");
+        for (name, arch, _, new) in DNF_UPGRADES {
+            println!(
+                " {:<24} {:<8} {:<22} {:<12} {:>4} M",
+                name.bright_white(),
+                arch,
+                new,
+                "fedora",
+                rng.gen_range(1..80)
+            );
+        }
+        println!("Removing:");
+        for name in DNF_REMOVED {
+            println!(
+                " {:<24} {:<8} {:<22} {:<12}",
+                name.bright_red(),
+                "x86_64",
+                "",
+                "@System"
+            );
+        }
+        let count = rng.gen_range(1..=DNF_SKIPPED.len());
+        let skipped: Vec<&str> = DNF_SKIPPED.choose_multiple(rng, count).copied().collect();
+        println!(
+            "Skipping packages with conflicts:\n(add '--best --allowerasing' to command line to force their upgrade):"
+        );
+        for name in &skipped {
+            println!(" {}", name.yellow());
+        }
+        println!();
+        println!("Transaction Summary");
+        println!("{}", "=".repeat(78));
+        println!("Upgrade  {} Packages", DNF_UPGRADES.len());
+        println!("Remove   {} Packages", DNF_REMOVED.len());
+        println!("Skip     {} Packages", skipped.len());
+        println!();
+        println!("Total download size: {:.1} G", rng.gen_range(1.2..3.4));
+        println!("DNF will only download packages for the transaction.");
+        print!("Is this ok [y/N]: ");
+        io::stdout().flush()?;
+        thread::sleep(Duration::from_millis(700));
+        println!("y");
+
+        let progress = ProgressBar::new(ProgressStyle::Equals);
+        progress.animate(
+            "Downloading Packages:",
+            rng.gen_range(2500..4000),
+            exit_check,
+        )?;
+        spinner.animate("Running transaction check", 800, exit_check)?;
+        spinner.animate("Running transaction test", 1200, exit_check)?;
+        println!("Download complete! Use 'dnf system-upgrade reboot' to start the upgrade.");
+        println!();
+
+        Self::command("sudo dnf system-upgrade reboot");
+        thread::sleep(Duration::from_millis(1000));
+        println!("{}", "Booting into offline upgrade environment...".dimmed());
+        thread::sleep(Duration::from_millis(1200));
+
+        let total = DNF_UPGRADES.len() * 2 + DNF_REMOVED.len();
+        let mut done = 0;
+        let steps = DNF_UPGRADES
+            .iter()
+            .map(|(name, arch, _, new)| ("Upgrading", format!("{}-{}.{}", name, new, arch)))
+            .chain(DNF_REMOVED.iter().map(|name| ("Erasing", name.to_string())))
+            .chain(
+                DNF_UPGRADES
+                    .iter()
+                    .map(|(name, arch, old, _)| ("Cleanup", format!("{}-{}.{}", name, old, arch))),
+            );
+        for (action, package) in steps {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            done += 1;
+            println!("  {:<10}: {:<50} {:>3}/{}", action, package, done, total);
+            if package.starts_with("openssh-server-") && action == "Upgrading" {
+                println!(
+                    "{}",
+                    "warning: /etc/ssh/sshd_config created as /etc/ssh/sshd_config.rpmnew".yellow()
+                );
+            }
+            thread::sleep(Duration::from_millis(rng.gen_range(80..300)));
+        }
+        for package in DNF_SCRIPTLETS {
+            println!("  {:<10}: {}", "Running scriptlet", package);
+            thread::sleep(Duration::from_millis(rng.gen_range(150..500)));
+        }
+        spinner.animate(
+            "dracut: *** Creating initramfs image file '/boot/initramfs-6.11.4-301.fc41.x86_64.img' ***",
+            1500,
+            exit_check,
+        )?;
+        println!("Complete!");
+        Ok(())
+    }
+}
+
+impl InstallationStage for DistUpgradeStage {
+    fn name(&self) -> &'static str {
+        "Distribution Upgrade"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        println!(
+            "{} Detected {}",
+            LogGenerator::timestamp().dimmed(),
+            self.host.os_name.bright_white()
+        );
+
+        match PackageManager::detect(&self.host.os_name) {
+            Some(PackageManager::Apt(release)) => self.apt(release, &mut rng, exit_check)?,
+            Some(PackageManager::Dnf) => self.dnf(&mut rng, exit_check)?,
+            None => println!(
+                "{} {}",
+                LogGenerator::timestamp().dimmed(),
+                "No supported package manager found, skipping distribution upgrade...".dimmed()
+            ),
+        }
+
+        thread::sleep(Duration::from_millis(500));
+        Ok(())
+    }
+}
@@ -1,13 +1,55 @@
 use super::InstallationStage;
+use crate::host_profile::{interface_driver, HostProfile};
 use crate::log_generator::LogGenerator;
 use crate::messages::DRIVERS;
 use colored::*;
 use rand::Rng;
 use std::io::{self, Write};
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-pub struct DriversStage;
+pub struct DriversStage {
+    host: Arc<HostProfile>,
+}
+
+impl DriversStage {
+    pub fn new(host: Arc<HostProfile>) -> Self {
+        Self { host }
+    }
+
+    /// The host's own disks and network interfaces, as (device, driver)
+    /// pairs. Fallback hardware invented by the host profile is left out.
+    fn host_devices(&self) -> Vec<(String, String)> {
+        let disks = self.host.disks.iter().filter(|_| self.host.disks_detected);
+        let disks = disks.map(|disk| {
+            (
+                format!("{} {} ({})", disk.bus, disk.model, disk.device),
+                disk.driver().to_string(),
+            )
+        });
+        let interfaces = self
+            .host
+            .interfaces
+            .iter()
+            .filter(|_| self.host.interfaces_detected);
+        let interfaces = interfaces.map(|interface| {
+            // Guess from the name only when the kernel doesn't say
+            let driver = interface_driver(interface).unwrap_or_else(|| {
+                if interface.starts_with("wl") {
+                    "iwlwifi"
+                } else if interface.starts_with("en") || interface.starts_with("eth") {
+                    "e1000e"
+                } else {
+                    "virtio_net"
+                }
+                .to_string()
+            });
+            (format!("Network interface {}", interface), driver)
+        });
+        disks.chain(interfaces).collect()
+    }
+}
 
 impl InstallationStage for DriversStage {
     fn name(&self) -> &'static str {
@@ -20,7 +62,16 @@ impl InstallationStage for DriversStage {
 
         let mut rng = rand::thread_rng();
 
-        for (device, driver) in DRIVERS {
+        // The period hardware list only stands in when nothing was detected
+        let mut devices = self.host_devices();
+        if devices.is_empty() {
+            devices = DRIVERS
+                .iter()
+                .map(|(device, driver)| (device.to_string(), driver.to_string()))
+                .collect();
+        }
+
+        for (device, driver) in &devices {
             if exit_check() {
                 return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
             }
@@ -1,13 +1,23 @@
 use super::InstallationStage;
+use crate::host_profile::HostProfile;
 use crate::log_generator::LogGenerator;
 use crate::ui::{ProgressBar, ProgressStyle};
 use colored::*;
 use rand::Rng;
 use std::io;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-pub struct FilesystemStage;
+pub struct FilesystemStage {
+    host: Arc<HostProfile>,
+}
+
+impl FilesystemStage {
+    pub fn new(host: Arc<HostProfile>) -> Self {
+        Self { host }
+    }
+}
 
 impl InstallationStage for FilesystemStage {
     fn name(&self) -> &'static str {
@@ -19,21 +29,26 @@ impl InstallationStage for FilesystemStage {
         println!();
 
         let mut rng = rand::thread_rng();
+        let partition = self.host.boot_disk().partition(2);
 
         println!(
             "{} {}",
             LogGenerator::timestamp().dimmed(),
-            "Creating ext4 filesystem on /dev/sda2...".bright_white()
+            format!("Creating ext4 filesystem on {}...", partition).bright_white()
         );
         thread::sleep(Duration::from_millis(600));
 
-        let blocks = rng.gen_range(50000000..100000000);
+        // Root partition takes most of the disk, less the ESP and some swap
+        let blocks = match self.host.boot_disk().capacity_gb {
+            0 => rng.gen_range(50000000..100000000),
+            gb => gb * 1_000_000_000 / 4096 * rng.gen_range(85..95) / 100,
+        };
         let inodes = blocks / 4;
 
         println!(
             "{} {}",
             LogGenerator::timestamp().dimmed(),
-            format!("mke2fs 1.45.5 (07-Jan-2020)").dimmed()
+            "mke2fs 1.45.5 (07-Jan-2020)".dimmed()
         );
         println!(
             "{} {}",
@@ -58,7 +73,7 @@ impl InstallationStage for FilesystemStage {
         println!(
             "{} {}",
             LogGenerator::timestamp().dimmed(),
-            format!("Superblock backups stored on blocks:").dimmed()
+            "Superblock backups stored on blocks:".dimmed()
         );
 
         let backup_blocks = [32768, 98304, 163840, 229376, 294912];
@@ -157,8 +172,14 @@ impl InstallationStage for FilesystemStage {
             println!(
                 "{} {}",
                 LogGenerator::timestamp().dimmed(),
-                "/dev/sda2: 11/2048000 files (0.0% non-contiguous), 200000/8192000 blocks"
-                    .bright_green()
+                format!(
+                    "{}: 11/{} files (0.0% non-contiguous), {}/{} blocks",
+                    partition,
+                    inodes,
+                    blocks / 40,
+                    blocks
+                )
+                .bright_green()
             );
         }
 
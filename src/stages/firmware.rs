@@ -0,0 +1,301 @@
+use super::InstallationStage;
+use crate::host_profile::HostProfile;
+use crate::log_generator::LogGenerator;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use colored::*;
+use crossterm::terminal;
+use rand::Rng;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// A device fwupd knows how to update
+struct FirmwareDevice {
+    name: String,
+    vendor: String,
+    current: &'static str,
+    update: &'static str,
+    /// Whether the update is applied by UEFI on the next boot
+    capsule: bool,
+}
+
+pub struct FirmwareStage {
+    host: Arc<HostProfile>,
+}
+
+impl FirmwareStage {
+    pub fn new(host: Arc<HostProfile>) -> Self {
+        Self { host }
+    }
+
+    fn devices(&self, rng: &mut rand::rngs::ThreadRng) -> Vec<FirmwareDevice> {
+        let cpu_vendor = if self.host.cpu_brand.contains("AMD") {
+            "Advanced Micro Devices, Inc."
+        } else {
+            "Intel Corporation"
+        };
+        let disk = self.host.boot_disk();
+        let disk_vendor = disk
+            .model
+            .split_whitespace()
+            .next()
+            .unwrap_or(disk.bus)
+            .to_string();
+        let mut devices = vec![
+            FirmwareDevice {
+                name: "System Firmware".to_string(),
+                vendor: "LENOVO".to_string(),
+                current: "0.1.47",
+                update: "0.1.52",
+                capsule: true,
+            },
+            FirmwareDevice {
+                name: format!("{} {}", disk.bus, disk.model),
+                vendor: disk_vendor,
+                current: "3B2QGXA7",
+                update: "4B2QGXA7",
+                capsule: false,
+            },
+            FirmwareDevice {
+                name: format!("{} (microcode)", self.host.cpu_brand),
+                vendor: cpu_vendor.to_string(),
+                current: "0x000000f4",
+                update: "0x000000fa",
+                capsule: true,
+            },
+            FirmwareDevice {
+                name: "TPM".to_string(),
+                vendor: "Infineon".to_string(),
+                current: "7.2.2.0",
+                update: "7.2.3.1",
+                capsule: true,
+            },
+        ];
+        if rng.gen_bool(0.7) {
+            devices.push(FirmwareDevice {
+                name: "Thunderbolt Controller".to_string(),
+                vendor: "Intel Corporation".to_string(),
+                current: "56.00",
+                update: "61.00",
+                capsule: false,
+            });
+        }
+        if rng.gen_bool(0.5) {
+            devices.push(FirmwareDevice {
+                name: "ThinkPad Thunderbolt 4 Dock".to_string(),
+                vendor: "Lenovo".to_string(),
+                current: "5.44.03",
+                update: "5.51.01",
+                capsule: false,
+            });
+        }
+        devices
+    }
+
+    fn guid() -> String {
+        let hex = &LogGenerator::hex_addr()[2..];
+        let more = &LogGenerator::hex_addr()[2..];
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &more[0..4],
+            &more[4..16]
+        )
+    }
+
+    /// Full-width warning banner, as printed before anything is flashed.
+    fn warning_banner(text: &str) {
+        let cols = terminal::size().map(|(c, _)| c as usize).unwrap_or(80);
+        let rule = "═".repeat(cols.max(text.len() + 4));
+        println!("{}", rule.bright_red());
+        println!(
+            "{}",
+            format!("{:^width$}", text, width = cols)
+                .bright_white()
+                .on_red()
+                .bold()
+        );
+        println!("{}", rule.bright_red());
+    }
+}
+
+impl InstallationStage for FirmwareStage {
+    fn name(&self) -> &'static str {
+        "Device Firmware Update"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+        let devices = self.devices(&mut rng);
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "$ fwupdmgr refresh && fwupdmgr get-devices".bright_white()
+        );
+        spinner.animate(
+            "Downloading https://cdn.fwupd.org/downloads/firmware.xml.zst",
+            1500,
+            exit_check,
+        )?;
+        println!(
+            "Successfully downloaded new metadata: {} local devices supported",
+            devices.len()
+        );
+        println!();
+
+        println!("{}", self.host.hostname.bright_white().bold());
+        for (i, device) in devices.iter().enumerate() {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            let last = i == devices.len() - 1;
+            let (branch, stem) = if last {
+                ("└─", "  ")
+            } else {
+                ("├─", "│ ")
+            };
+            println!("{}{}:", branch.dimmed(), device.name.bright_white());
+            println!(
+                "{}    Device ID:       {}",
+                stem.dimmed(),
+                &LogGenerator::hex_addr()[2..]
+            );
+            println!("{}    Current version: {}", stem.dimmed(), device.current);
+            println!("{}    Vendor:          {}", stem.dimmed(), device.vendor);
+            println!("{}    GUID:            {}", stem.dimmed(), Self::guid());
+            println!(
+                "{}    Device Flags:    • {}",
+                stem.dimmed(),
+                if device.capsule {
+                    "Updatable · Needs a reboot after installation"
+                } else {
+                    "Updatable · Supported on remote server"
+                }
+            );
+            println!("{}", stem.dimmed());
+            thread::sleep(Duration::from_millis(rng.gen_range(150..350)));
+        }
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "$ fwupdmgr update".bright_white()
+        );
+        println!();
+        for device in &devices {
+            println!(
+                "{} {} → {}",
+                format!("Upgrade {} from", device.name).bright_white(),
+                device.current.dimmed(),
+                device.update.bright_green()
+            );
+        }
+        println!();
+        println!("Perform operation? [Y|n]: {}", "Y".bright_white());
+        thread::sleep(Duration::from_millis(600));
+        println!();
+
+        Self::warning_banner("DO NOT TURN OFF YOUR COMPUTER OR REMOVE THE AC ADAPTER");
+        println!();
+        thread::sleep(Duration::from_millis(800));
+
+        let progress = ProgressBar::new(ProgressStyle::Block);
+        let mut staged = 0;
+        for device in &devices {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            let label = format!("{:<36}", truncate(&device.name, 36));
+            if device.capsule {
+                progress.animate(
+                    &format!("Staging capsule   {}", label),
+                    rng.gen_range(800..1800),
+                    exit_check,
+                )?;
+                staged += 1;
+            } else {
+                progress.animate(
+                    &format!("Writing           {}", label),
+                    rng.gen_range(2000..4500),
+                    exit_check,
+                )?;
+                progress.animate(
+                    &format!("Verifying         {}", label),
+                    rng.gen_range(600..1200),
+                    exit_check,
+                )?;
+            }
+        }
+
+        println!();
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!(
+                "{} capsule(s) staged to /boot/efi/EFI/UpdateCapsule/fwupd-{}.cap",
+                staged,
+                Self::guid()
+            )
+            .dimmed()
+        );
+        println!(
+            "{}",
+            "An update requires a reboot to complete. Restart now? [y|N]: y".bright_white()
+        );
+        thread::sleep(Duration::from_millis(1000));
+        spinner.animate("Rebooting...", 1500, exit_check)?;
+
+        // The firmware's own update screen, drawn before any OS is loaded
+        println!();
+        println!(
+            "{}",
+            format!("{:^80}", "UEFI Firmware Update")
+                .bright_white()
+                .bold()
+        );
+        println!();
+        for device in devices.iter().filter(|d| d.capsule) {
+            print!(
+                "  {} {} ",
+                "Updating".bright_white(),
+                truncate(&device.name, 40)
+            );
+            io::stdout().flush()?;
+            let steps = 20;
+            for _ in 0..steps {
+                if exit_check() {
+                    return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+                }
+                print!("{}", "▮".bright_blue());
+                io::stdout().flush()?;
+                thread::sleep(Duration::from_millis(rng.gen_range(60..200)));
+            }
+            println!(" {}", "Done".bright_green());
+        }
+        println!();
+        println!(
+            "{}",
+            format!("{:^80}", "Update successful. Rebooting...").bright_green()
+        );
+        thread::sleep(Duration::from_millis(800));
+
+        Ok(())
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(width - 1).collect();
+        format!("{}…", cut)
+    }
+}
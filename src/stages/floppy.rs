@@ -0,0 +1,282 @@
+use super::InstallationStage;
+use crate::log_generator::LogGenerator;
+use crate::ui::{prompt_key, ProgressBar, ProgressStyle};
+use colored::*;
+use crossterm::event::{self, Event, KeyCode};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Compressed files on the distribution disks
+const FILES: &[&str] = &[
+    "KRNL386.EX_",
+    "GDI.EX_",
+    "USER.EX_",
+    "PROGMAN.EX_",
+    "WIN.CO_",
+    "VGA.DR_",
+    "SOUND.DR_",
+    "MMSYSTEM.DL_",
+    "COMMDLG.DL_",
+    "SHELL.DL_",
+    "WINFILE.EX_",
+    "CONTROL.EX_",
+    "SOL.EX_",
+    "WINMINE.EX_",
+    "WRITE.EX_",
+    "PBRUSH.EX_",
+    "TERMINAL.EX_",
+    "ARIAL.TT_",
+    "TIMES.TT_",
+    "SMARTDRV.EX_",
+];
+
+/// (line, added by setup)
+const CONFIG_SYS: &[(&str, bool)] = &[
+    ("DEVICE=C:\\WINDOWS\\HIMEM.SYS", true),
+    ("DEVICE=C:\\WINDOWS\\EMM386.EXE NOEMS", true),
+    ("DOS=HIGH,UMB", false),
+    ("FILES=40", true),
+    ("BUFFERS=30", false),
+    ("STACKS=9,256", true),
+    ("DEVICEHIGH=C:\\DOS\\SETVER.EXE", false),
+];
+
+const AUTOEXEC_BAT: &[(&str, bool)] = &[
+    ("@ECHO OFF", false),
+    ("PROMPT $p$g", false),
+    ("PATH C:\\WINDOWS;C:\\DOS", true),
+    ("SET TEMP=C:\\WINDOWS\\TEMP", true),
+    ("LH C:\\WINDOWS\\SMARTDRV.EXE", true),
+    ("LH C:\\MOUSE\\MOUSE.COM", false),
+    ("WIN", true),
+];
+
+/// How long the disk prompt waits for a key before carrying on by itself
+const DISK_PROMPT_TIMEOUT_MS: u64 = 2500;
+
+/// Chance that one disk in the set turns out to be bad
+const DRIVE_ERROR_CHANCE: f64 = 0.3;
+
+/// What the user answered to "Abort, Retry, Fail?"
+enum DriveError {
+    Abort,
+    Retry,
+    Fail,
+}
+
+pub struct FloppyStage;
+
+impl FloppyStage {
+    /// Waits for ENTER (or any key) like setup does, but gives up waiting
+    /// after a moment so an unattended run keeps going.
+    fn wait_for_disk(&self, disk: u32, total: u32) -> io::Result<()> {
+        println!();
+        println!(
+            "{}",
+            format!("Please insert Disk {} of {} into drive A:", disk, total)
+                .bright_white()
+                .bold()
+        );
+        print!("{}", "Press ENTER when ready. ".bright_white());
+        io::stdout().flush()?;
+
+        let steps = DISK_PROMPT_TIMEOUT_MS / 100;
+        for _ in 0..steps {
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key_event) = event::read()? {
+                    // Don't let the prompt swallow the user's way out
+                    if key_event.code == KeyCode::Char('c')
+                        && key_event.modifiers.contains(event::KeyModifiers::CONTROL)
+                    {
+                        println!();
+                        return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+                    }
+                    break;
+                }
+            }
+        }
+        println!();
+        Ok(())
+    }
+
+    fn prompt_drive_error(&self) -> io::Result<DriveError> {
+        println!();
+        println!("{}", "General failure reading drive A".bright_white());
+        let question = "Abort, Retry, Fail?".bright_white().bold().to_string();
+        let answer = match prompt_key(&question, &['A', 'R', 'F'])? {
+            0 => DriveError::Abort,
+            1 => DriveError::Retry,
+            _ => DriveError::Fail,
+        };
+        let pause = if matches!(answer, DriveError::Retry) {
+            1000
+        } else {
+            500
+        };
+        thread::sleep(Duration::from_millis(pause));
+        Ok(answer)
+    }
+
+    fn edit_file(&self, path: &str, lines: &[(&str, bool)]) {
+        println!();
+        println!(
+            "{}",
+            format!(" File  Edit  Search  Options{:>44}", path)
+                .black()
+                .on_white()
+        );
+        for (line, added) in lines {
+            if *added {
+                println!("{}", format!(" {:<72}", line).bright_yellow().on_blue());
+            } else {
+                println!("{}", format!(" {:<72}", line).white().on_blue());
+            }
+            thread::sleep(Duration::from_millis(150));
+        }
+        println!(
+            "{}",
+            format!(
+                " MS-DOS Editor  <F1=Help> Press ALT to activate menus{:>20}",
+                ""
+            )
+            .black()
+            .on_cyan()
+        );
+        thread::sleep(Duration::from_millis(800));
+    }
+}
+
+impl InstallationStage for FloppyStage {
+    fn name(&self) -> &'static str {
+        "Floppy Disk Installation"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let total = rng.gen_range(7..14);
+
+        println!("Starting MS-DOS...");
+        thread::sleep(Duration::from_millis(1200));
+        println!();
+        println!("A:\\>{}", "SETUP".bright_white());
+        thread::sleep(Duration::from_millis(600));
+        println!();
+        println!(
+            "{}",
+            format!("{:<78}", " Windows Setup").bright_white().on_blue()
+        );
+        println!(
+            "{}",
+            format!("{:<78}", " Setup is copying files to C:\\WINDOWS")
+                .white()
+                .on_blue()
+        );
+
+        let bad_disk = rng
+            .gen_bool(DRIVE_ERROR_CHANCE)
+            .then(|| rng.gen_range(1..=total));
+
+        let progress = ProgressBar::new(ProgressStyle::Block);
+        for disk in 1..=total {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            if disk > 1 {
+                self.wait_for_disk(disk, total)?;
+            }
+
+            let count = rng.gen_range(2..5);
+            let files: Vec<&str> = FILES.choose_multiple(&mut rng, count).copied().collect();
+            for (i, file) in files.into_iter().enumerate() {
+                if i == 0 && bad_disk == Some(disk) {
+                    match self.prompt_drive_error()? {
+                        DriveError::Abort => {
+                            println!();
+                            println!(
+                                "{} {}",
+                                LogGenerator::timestamp().dimmed(),
+                                "Setup was not completed. Run SETUP again to install Windows."
+                                    .bright_red()
+                            );
+                            return Ok(());
+                        }
+                        DriveError::Fail => {
+                            println!();
+                            println!(
+                                "{}",
+                                format!("Setup could not copy {}. You can install it later.", file)
+                                    .yellow()
+                            );
+                            continue;
+                        }
+                        DriveError::Retry => {}
+                    }
+                }
+                progress.animate(
+                    &format!("  Copying: {:<14}", file),
+                    rng.gen_range(700..1800),
+                    exit_check,
+                )?;
+            }
+        }
+
+        println!();
+        println!(
+            "{}",
+            "Setup will now modify your CONFIG.SYS and AUTOEXEC.BAT files.".bright_white()
+        );
+        thread::sleep(Duration::from_millis(700));
+        self.edit_file("C:\\CONFIG.SYS", CONFIG_SYS);
+        self.edit_file("C:\\AUTOEXEC.BAT", AUTOEXEC_BAT);
+        println!();
+        println!(
+            "{}",
+            "The old files were saved as CONFIG.OLD and AUTOEXEC.OLD.".dimmed()
+        );
+
+        println!();
+        println!(
+            "{}",
+            "Windows Setup is complete. Remove any disks from their drives, then press CTRL+ALT+DEL to restart your computer."
+                .bright_white()
+        );
+        thread::sleep(Duration::from_millis(1500));
+        println!();
+
+        println!("Starting MS-DOS...");
+        thread::sleep(Duration::from_millis(800));
+        println!();
+        for line in [
+            "HIMEM: DOS XMS Driver, Version 3.10 - 07/16/92",
+            "Extended Memory Specification (XMS) Version 3.0",
+            "Copyright 1988-1992 Microsoft Corp.",
+            "",
+            "Installed A20 handler number 1.",
+            "64K High Memory Area is available.",
+            "",
+            "MICROSOFT Expanded Memory Manager 386  Version 4.45",
+            "Copyright Microsoft Corporation 1986, 1992",
+            "",
+            "    EMM386 successfully installed.",
+        ] {
+            println!("{}", line);
+            thread::sleep(Duration::from_millis(rng.gen_range(40..160)));
+        }
+        println!();
+        println!(
+            "Microsoft SMARTDrive Disk Cache version 4.0\nCopyright 1991,1992 Microsoft Corp.\n\nCache size: {} bytes",
+            [1_048_576, 2_097_152][rng.gen_range(0..2)]
+        );
+        println!();
+        println!("C:\\>{}", "WIN".bright_white());
+        thread::sleep(Duration::from_millis(1200));
+
+        Ok(())
+    }
+}
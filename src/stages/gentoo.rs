@@ -0,0 +1,444 @@
+use super::InstallationStage;
+use crate::build_logs::BuildLogs;
+use crate::config::GentooConfig;
+use crate::log_generator::LogGenerator;
+use crate::ui::Spinner;
+use colored::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// (atom, version, distfile size in KiB)
+const PACKAGES: &[(&str, &str, u32)] = &[
+    ("sys-libs/glibc", "2.39-r6", 18_720),
+    ("sys-devel/gcc", "13.3.1_p20240614", 86_243),
+    ("sys-devel/binutils", "2.42-r1", 26_408),
+    ("dev-libs/openssl", "3.1.4", 15_732),
+    ("dev-lang/python", "3.12.4", 20_122),
+    ("dev-lang/perl", "5.40.0", 13_478),
+    ("dev-lang/rust", "1.79.0", 262_014),
+    ("llvm-core/llvm", "18.1.8", 129_416),
+    ("sys-apps/systemd", "255.7", 14_652),
+    ("sys-apps/portage", "3.0.65", 1_301),
+    ("dev-libs/boost", "1.85.0", 125_843),
+    ("media-libs/mesa", "24.1.7", 29_842),
+    ("x11-base/xorg-server", "21.1.13", 5_014),
+    ("dev-qt/qtbase", "6.7.2", 47_936),
+    ("kde-frameworks/kio", "6.4.0", 3_387),
+    ("www-client/firefox", "128.1.0", 541_376),
+    ("www-client/chromium", "127.0.6533.88", 3_312_642),
+    ("app-office/libreoffice", "24.2.5.2", 263_740),
+    ("media-video/ffmpeg", "6.1.1-r8", 10_176),
+    ("net-libs/webkit-gtk", "2.44.2", 38_254),
+    ("dev-libs/glib", "2.78.6", 5_338),
+    ("x11-libs/gtk+", "3.24.42", 21_742),
+    ("sys-kernel/gentoo-sources", "6.6.47", 137_442),
+    ("app-editors/vim", "9.1.0418", 17_482),
+    ("dev-vcs/git", "2.45.2", 7_385),
+    ("net-misc/curl", "8.8.0", 2_640),
+    ("sys-libs/zlib", "1.3.1-r1", 1_286),
+    ("app-arch/zstd", "1.5.6", 2_343),
+];
+
+const USE_FLAGS: &[&str] = &[
+    "X",
+    "wayland",
+    "pulseaudio",
+    "pipewire",
+    "vaapi",
+    "vulkan",
+    "lto",
+    "pgo",
+    "gtk",
+    "qt6",
+    "systemd",
+    "-bindist",
+    "jit",
+    "zstd",
+    "clang",
+];
+
+pub struct GentooStage {
+    config: GentooConfig,
+    build_logs: BuildLogs,
+}
+
+impl GentooStage {
+    pub fn new(config: GentooConfig) -> Self {
+        Self {
+            config,
+            build_logs: BuildLogs::load(),
+        }
+    }
+
+    fn use_flags(rng: &mut rand::rngs::ThreadRng) -> String {
+        let count = rng.gen_range(2..6);
+        let mut flags: Vec<&str> = USE_FLAGS.choose_multiple(rng, count).copied().collect();
+        flags.sort_by_key(|flag| flag.trim_start_matches('-'));
+        flags.join(" ")
+    }
+
+    /// A random dependency graph over `PACKAGES`, as each package's list of
+    /// dependencies. Packages only depend on ones ranked below them, so the
+    /// graph is always acyclic.
+    fn dependency_graph(rng: &mut rand::rngs::ThreadRng) -> Vec<Vec<usize>> {
+        let mut rank: Vec<usize> = (0..PACKAGES.len()).collect();
+        rank.shuffle(rng);
+        (0..PACKAGES.len())
+            .map(|package| {
+                let below: Vec<usize> = (0..PACKAGES.len())
+                    .filter(|&other| rank[other] < rank[package])
+                    .collect();
+                let count = rng.gen_range(0..=3).min(below.len());
+                below.choose_multiple(rng, count).copied().collect()
+            })
+            .collect()
+    }
+
+    /// Resolve every package in a random order, dependencies first.
+    fn merge_order(graph: &[Vec<usize>], rng: &mut rand::rngs::ThreadRng) -> Vec<usize> {
+        fn visit(package: usize, graph: &[Vec<usize>], order: &mut Vec<usize>) {
+            if order.contains(&package) {
+                return;
+            }
+            for &dependency in &graph[package] {
+                visit(dependency, graph, order);
+            }
+            order.push(package);
+        }
+
+        let mut roots: Vec<usize> = (0..graph.len()).collect();
+        roots.shuffle(rng);
+        let mut order = Vec::new();
+        for root in roots {
+            visit(root, graph, &mut order);
+        }
+        order
+    }
+
+    fn use_conflict(&self, graph: &[Vec<usize>], rng: &mut rand::rngs::ThreadRng) {
+        // A flag the parent needs on one of its own dependencies
+        let parents: Vec<usize> = (0..graph.len())
+            .filter(|&package| !graph[package].is_empty())
+            .collect();
+        let (parent, dependency) = match parents.choose(rng) {
+            Some(&parent) => (parent, *graph[parent].choose(rng).unwrap()),
+            None => {
+                let pair = rand::seq::index::sample(rng, PACKAGES.len(), 2);
+                (pair.index(0), pair.index(1))
+            }
+        };
+        let (dependency, version, _) = PACKAGES[dependency];
+        let (parent, parent_version, _) = PACKAGES[parent];
+        let flag = USE_FLAGS[rng.gen_range(0..USE_FLAGS.len())].trim_start_matches('-');
+
+        println!();
+        println!(
+            "{}",
+            "!!! The following USE changes are necessary to proceed:".bright_red()
+        );
+        println!(
+            "{}",
+            " (see \"package.use\" in the portage(5) man page for more details)".dimmed()
+        );
+        println!(
+            "{}",
+            format!("# required by {}-{}::gentoo", parent, parent_version).dimmed()
+        );
+        println!("{}", "# required by @world (argument)".dimmed());
+        println!(">={}-{} {}", dependency, version, flag.bright_yellow());
+        println!();
+        println!(
+            "{}",
+            "Autounmask changes successfully written.".bright_green()
+        );
+        println!(
+            "{}",
+            " * IMPORTANT: config file '/etc/portage/package.use/zz-autounmask' needs updating."
+                .yellow()
+        );
+        thread::sleep(Duration::from_millis(1200));
+        println!("{}", "# dispatch-conf".bright_white());
+        println!(
+            "{}",
+            ">> (1 of 1) -- /etc/portage/package.use/zz-autounmask".dimmed()
+        );
+        println!("{}", ">> q quit, u use new, z zap new: u".dimmed());
+        thread::sleep(Duration::from_millis(800));
+        println!();
+    }
+
+    fn compile(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let lines = rng.gen_range(self.config.build_lines_range.clone());
+        for line in self.build_logs.random_batch(lines) {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            println!("{}", line.dimmed());
+            thread::sleep(Duration::from_millis(
+                rng.gen_range(self.config.build_line_delay_range.clone()),
+            ));
+        }
+        Ok(())
+    }
+
+    fn emerge(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        index: usize,
+        total: usize,
+        package: (&str, &str, u32),
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let (atom, version, _) = package;
+        let name = atom.split('/').nth(1).unwrap_or(atom);
+        let pf = format!("{}-{}", name, version);
+        let workdir = format!("/var/tmp/portage/{}-{}/work/{}", atom, version, pf);
+
+        println!(
+            "{} {} {}",
+            ">>>".bright_green(),
+            format!("Emerging ({} of {})", index, total).bright_white(),
+            format!("{}-{}::gentoo", atom, version).bright_green()
+        );
+        thread::sleep(Duration::from_millis(300));
+        println!(" {} checking {}.tar.xz ;-) ...", "*".bright_green(), pf);
+        println!("{} Unpacking source...", ">>>".bright_green());
+        thread::sleep(Duration::from_millis(rng.gen_range(200..500)));
+        println!(
+            "{} Source unpacked in /var/tmp/portage/{}-{}/work",
+            ">>>".bright_green(),
+            atom,
+            version
+        );
+        println!(
+            "{} Preparing source in {} ...",
+            ">>>".bright_green(),
+            workdir
+        );
+        println!(
+            "{} Configuring source in {} ...",
+            ">>>".bright_green(),
+            workdir
+        );
+        thread::sleep(Duration::from_millis(rng.gen_range(200..600)));
+        println!(
+            "{} Compiling source in {} ...",
+            ">>>".bright_green(),
+            workdir
+        );
+
+        self.compile(rng, exit_check)?;
+
+        if rng.gen_bool(self.config.failure_rate_compile) {
+            println!();
+            println!(
+                " {} {}",
+                "*".bright_red(),
+                format!(
+                    "ERROR: {}-{}::gentoo failed (compile phase):",
+                    atom, version
+                )
+                .bright_red()
+            );
+            println!(" {}   emake failed", "*".bright_red());
+            println!(" {}", "*".bright_red());
+            println!(
+                " {} The complete build log is located at '/var/tmp/portage/{}-{}/temp/build.log'.",
+                "*".bright_red(),
+                atom,
+                version
+            );
+            println!();
+            println!(
+                "{}",
+                format!(
+                    "!!! ERROR: {}-{}::gentoo failed (compile phase)",
+                    atom, version
+                )
+                .bright_red()
+                .bold()
+            );
+            println!(
+                " {} Messages for package {}:",
+                "*".bright_yellow(),
+                format!("{}-{}", atom, version).bright_white()
+            );
+            thread::sleep(Duration::from_millis(1500));
+
+            println!();
+            println!("{}", "# MAKEOPTS=\"-j1\" emerge --resume".bright_white());
+            thread::sleep(Duration::from_millis(600));
+            println!(
+                "{} {}",
+                "*".bright_green(),
+                "Resuming merge from the failed package...".bright_white()
+            );
+            println!(
+                "{} Compiling source in {} ...",
+                ">>>".bright_green(),
+                workdir
+            );
+            self.compile(rng, exit_check)?;
+        }
+
+        println!("{} Source compiled.", ">>>".bright_green());
+        println!(
+            "{} Install {}-{} into /var/tmp/portage/{}-{}/image",
+            ">>>".bright_green(),
+            atom,
+            version,
+            atom,
+            version
+        );
+        thread::sleep(Duration::from_millis(rng.gen_range(200..500)));
+        println!(
+            "{} {}",
+            ">>>".bright_green(),
+            format!(
+                "Completed ({} of {}) {}-{}::gentoo",
+                index, total, atom, version
+            )
+            .bright_white()
+        );
+        println!();
+        Ok(())
+    }
+}
+
+impl InstallationStage for GentooStage {
+    fn name(&self) -> &'static str {
+        "Gentoo @world Update"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "# emerge --ask --verbose --update --deep --newuse @world".bright_white()
+        );
+        println!();
+        spinner.animate(
+            "Calculating dependencies...",
+            self.config.resolve_time,
+            exit_check,
+        )?;
+        let graph = Self::dependency_graph(&mut rng);
+        println!("{}", "... done!".bright_green());
+
+        if rng.gen_bool(self.config.use_conflict_chance) {
+            self.use_conflict(&graph, &mut rng);
+            spinner.animate(
+                "Calculating dependencies...",
+                self.config.resolve_time / 2,
+                exit_check,
+            )?;
+            println!("{}", "... done!".bright_green());
+        }
+        println!();
+        println!("These are the packages that would be merged, in order:");
+        println!();
+
+        // A prefix of the merge order still has every dependency before the
+        // package that needs it
+        let shown = rng
+            .gen_range(self.config.shown_packages_range.clone())
+            .min(PACKAGES.len());
+        let total = rng
+            .gen_range(self.config.world_size_range.clone())
+            .max(shown);
+        let packages: Vec<(&str, &str, u32)> = Self::merge_order(&graph, &mut rng)
+            .into_iter()
+            .take(shown)
+            .map(|package| PACKAGES[package])
+            .collect();
+
+        // Spread the shown packages across the whole merge list
+        let mut positions: Vec<usize> = rand::seq::index::sample(&mut rng, total, shown)
+            .into_iter()
+            .map(|i| i + 1)
+            .collect();
+        positions.sort();
+
+        for (atom, version, size) in &packages {
+            println!(
+                "[{}     {}  ] {}-{}::gentoo  USE=\"{}\" {} KiB",
+                "ebuild".bright_green(),
+                if rng.gen_bool(0.8) {
+                    "U".bright_cyan()
+                } else {
+                    "N".bright_green()
+                },
+                atom.bright_green(),
+                version.bright_green(),
+                Self::use_flags(&mut rng).bright_red(),
+                size
+            );
+        }
+        println!("{}", format!("... and {} more", total - shown).dimmed());
+
+        let upgrades = total * rng.gen_range(80..95) / 100;
+        let download_kib: u64 = rng.gen_range(800_000..4_000_000);
+        println!();
+        println!(
+            "Total: {} packages ({} upgrades, {} new), Size of downloads: {} KiB",
+            total,
+            upgrades,
+            total - upgrades,
+            download_kib
+        );
+        println!();
+        println!(
+            "Would you like to merge these packages? [{}/{}] {}",
+            "Yes".bright_green(),
+            "No".bright_red(),
+            "Yes".bright_white()
+        );
+        thread::sleep(Duration::from_millis(600));
+        println!();
+
+        for (position, package) in positions.into_iter().zip(packages) {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            self.emerge(&mut rng, position, total, package, exit_check)?;
+        }
+
+        println!("{} Auto-cleaning packages...", ">>>".bright_green());
+        thread::sleep(Duration::from_millis(500));
+        println!(
+            "{} No outdated packages were found on your system.",
+            ">>>".bright_green()
+        );
+        println!();
+        println!(
+            " {} GNU info directory index is up-to-date.",
+            "*".bright_green()
+        );
+        println!(
+            " {} IMPORTANT: {} news items need reading for repository 'gentoo'.",
+            "*".bright_yellow(),
+            rng.gen_range(2..20)
+        );
+        println!(
+            " {} Use {} to view news items.",
+            "*".bright_yellow(),
+            "eselect news read".bright_white()
+        );
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
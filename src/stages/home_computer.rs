@@ -0,0 +1,226 @@
+use super::InstallationStage;
+use crate::ui::{ProgressBar, ProgressStyle};
+use colored::*;
+use crossterm::terminal;
+use rand::Rng;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Width of the stripe band, capped so it reads like a TV border
+const MAX_STRIPE_WIDTH: usize = 72;
+
+/// A machine to boot, with its tape loader's two stripe phases: the pilot
+/// tone colours, then the data colours
+struct Machine {
+    name: &'static str,
+    pilot: (Color, Color),
+    data: (Color, Color),
+}
+
+const MACHINES: &[Machine] = &[
+    Machine {
+        name: "Commodore 64",
+        pilot: (Color::Cyan, Color::Red),
+        data: (Color::Yellow, Color::Blue),
+    },
+    Machine {
+        name: "ZX Spectrum",
+        pilot: (Color::Red, Color::Cyan),
+        data: (Color::Blue, Color::Yellow),
+    },
+    Machine {
+        name: "Apple ][",
+        pilot: (Color::White, Color::Black),
+        data: (Color::BrightWhite, Color::Black),
+    },
+];
+
+pub struct HomeComputerStage;
+
+impl HomeComputerStage {
+    fn stripe_width() -> usize {
+        terminal::size()
+            .map(|(c, _)| c as usize)
+            .unwrap_or(80)
+            .min(MAX_STRIPE_WIDTH)
+    }
+
+    /// Scroll bands of alternating colour, the way the screen border looked
+    /// while a tape was being read.
+    fn border_stripes(
+        colors: (Color, Color),
+        lines: usize,
+        thin: bool,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let width = Self::stripe_width();
+        for _ in 0..lines {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            let mut line = String::new();
+            let mut used = 0;
+            let mut toggle = rng.gen_bool(0.5);
+            while used < width {
+                let band = if thin {
+                    rng.gen_range(1..4)
+                } else {
+                    rng.gen_range(6..14)
+                }
+                .min(width - used);
+                let color = if toggle { colors.0 } else { colors.1 };
+                line.push_str(&" ".repeat(band).on_color(color).to_string());
+                used += band;
+                toggle = !toggle;
+            }
+            println!("{}", line);
+            thread::sleep(Duration::from_millis(60));
+        }
+        Ok(())
+    }
+
+    fn banner(machine: &Machine) {
+        match machine.name {
+            "Commodore 64" => {
+                let width = Self::stripe_width();
+                for line in [
+                    String::new(),
+                    format!("{:^width$}", "**** COMMODORE 64 BASIC V2 ****"),
+                    String::new(),
+                    format!("{:^width$}", "64K RAM SYSTEM  38911 BASIC BYTES FREE"),
+                    String::new(),
+                    format!("{:<width$}", "READY."),
+                ] {
+                    println!("{}", line.bright_blue().on_blue());
+                }
+            }
+            "ZX Spectrum" => {
+                let width = Self::stripe_width();
+                for _ in 0..3 {
+                    println!("{}", " ".repeat(width).on_white());
+                }
+                println!(
+                    "{}",
+                    format!("{:<width$}", "© 1982 Sinclair Research Ltd")
+                        .black()
+                        .on_white()
+                );
+            }
+            _ => {
+                println!();
+                println!("{:^40}", "APPLE ][".bright_white());
+                println!();
+                println!("{}", "]".bright_white());
+            }
+        }
+    }
+
+    /// Type a command at the BASIC prompt.
+    fn type_command(command: &str, rng: &mut rand::rngs::ThreadRng) -> io::Result<()> {
+        for c in command.chars() {
+            print!("{}", c.to_string().bright_white());
+            io::stdout().flush()?;
+            thread::sleep(Duration::from_millis(rng.gen_range(80..220)));
+        }
+        println!();
+        Ok(())
+    }
+
+    fn load(
+        machine: &Machine,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let (command, found) = match machine.name {
+            "Commodore 64" => ("LOAD", "FOUND CURSED INSTALL"),
+            "ZX Spectrum" => ("LOAD \"\"", "Program: CURSED"),
+            _ => ("LOAD", ""),
+        };
+        Self::type_command(command, rng)?;
+        if machine.name == "Commodore 64" {
+            println!("PRESS PLAY ON TAPE");
+            thread::sleep(Duration::from_millis(1200));
+            println!("OK");
+            println!();
+            println!("SEARCHING");
+        }
+
+        Self::border_stripes(machine.pilot, rng.gen_range(6..10), false, rng, exit_check)?;
+        if !found.is_empty() {
+            println!("{}", found.bright_white());
+        }
+        Self::border_stripes(machine.data, rng.gen_range(8..14), true, rng, exit_check)?;
+
+        // Loading screens showed a countdown so you knew whether to make tea
+        let total_seconds: u32 = rng.gen_range(180..600);
+        let steps = 40;
+        let tape = ProgressBar::new(ProgressStyle::Tape);
+        for step in 0..=steps {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            let progress = step as f32 / steps as f32;
+            let remaining = total_seconds - (total_seconds as f32 * progress) as u32;
+            print!(
+                "\r{} {}  {}",
+                "LOADING".bright_white(),
+                tape.render(progress),
+                format!("{}:{:02} remaining", remaining / 60, remaining % 60).dimmed()
+            );
+            io::stdout().flush()?;
+            thread::sleep(Duration::from_millis(rng.gen_range(60..160)));
+        }
+        println!();
+
+        // The classic C64 disappointment
+        if machine.name == "Commodore 64" && rng.gen_bool(0.2) {
+            println!("?LOAD  ERROR");
+            println!("READY.");
+            thread::sleep(Duration::from_millis(1000));
+            println!("{}", "(rewinds tape, adjusts azimuth screw)".dimmed());
+            thread::sleep(Duration::from_millis(1500));
+            return Self::load(machine, rng, exit_check);
+        }
+
+        match machine.name {
+            "Commodore 64" => {
+                println!("READY.");
+                Self::type_command("RUN", rng)?;
+            }
+            "ZX Spectrum" => println!("0 OK, 0:1"),
+            _ => {
+                println!("]");
+                Self::type_command("RUN", rng)?;
+            }
+        }
+        println!();
+        Ok(())
+    }
+}
+
+impl InstallationStage for HomeComputerStage {
+    fn name(&self) -> &'static str {
+        "Home Computer Boot"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+
+        for machine in MACHINES {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            println!("{}", format!("[ {} ]", machine.name).dimmed());
+            Self::banner(machine);
+            thread::sleep(Duration::from_millis(800));
+            Self::load(machine, &mut rng, exit_check)?;
+        }
+
+        Ok(())
+    }
+}
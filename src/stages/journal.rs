@@ -0,0 +1,443 @@
+use super::InstallationStage;
+use crate::host_profile::HostProfile;
+use crate::kernel_logs::KernelLogs;
+use crate::log_generator::LogGenerator;
+use crate::ui::Spinner;
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use colored::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// (unit, description, process, what the process logs once it is up)
+const UNITS: &[(&str, &str, &str, &str)] = &[
+    (
+        "systemd-journald.service",
+        "Journal Service",
+        "systemd-journald",
+        "System Journal (/var/log/journal/) is 1.2G, max 4.0G, 2.8G free.",
+    ),
+    (
+        "systemd-udevd.service",
+        "Rule-based Manager for Device Events and Files",
+        "systemd-udevd",
+        "Using default interface naming scheme 'v255'.",
+    ),
+    (
+        "dbus.service",
+        "D-Bus System Message Bus",
+        "dbus-daemon",
+        "[system] Successfully activated service 'org.freedesktop.systemd1'",
+    ),
+    (
+        "NetworkManager.service",
+        "Network Manager",
+        "NetworkManager",
+        "<info>  [1729330000.1234] manager: NetworkManager state is now CONNECTED_GLOBAL",
+    ),
+    (
+        "NetworkManager-wait-online.service",
+        "Network Manager Wait Online",
+        "nm-online",
+        "Connecting...............   30s [online]",
+    ),
+    (
+        "systemd-resolved.service",
+        "Network Name Resolution",
+        "systemd-resolved",
+        "Using degraded feature set UDP instead of UDP+EDNS0 for DNS server 192.168.1.1.",
+    ),
+    (
+        "ssh.service",
+        "OpenBSD Secure Shell server",
+        "sshd",
+        "Server listening on 0.0.0.0 port 22.",
+    ),
+    (
+        "docker.service",
+        "Docker Application Container Engine",
+        "dockerd",
+        "API listen on /run/docker.sock",
+    ),
+    (
+        "snapd.service",
+        "Snap Daemon",
+        "snapd",
+        "daemon.go:247: started snapd/2.63 (series 16; classic) ubuntu/24.04 (amd64) linux/6.8.0-41-generic.",
+    ),
+    (
+        "cups.service",
+        "CUPS Scheduler",
+        "cupsd",
+        "REQUEST localhost - - \"POST / HTTP/1.1\" 200 183 Create-Printer-Subscriptions successful-ok",
+    ),
+    (
+        "gdm.service",
+        "GNOME Display Manager",
+        "gdm",
+        "Enabling debugging",
+    ),
+];
+
+/// Units that might fail on the first try and get restarted, with the error
+/// their process logs before exiting
+const FLAKY_UNITS: &[(&str, &str)] = &[
+    (
+        "docker.service",
+        "failed to start daemon: error while opening volume store metadata database: timeout",
+    ),
+    (
+        "snapd.service",
+        "cannot run daemon: state startup timed out after 30s",
+    ),
+    (
+        "cups.service",
+        "Unable to open listen socket for address [v1.::1]:631 - Address already in use.",
+    ),
+];
+
+/// Which units in `UNITS` make up the critical chain, outermost last
+const CRITICAL_CHAIN: &[&str] = &[
+    "dbus.service",
+    "NetworkManager.service",
+    "NetworkManager-wait-online.service",
+    "docker.service",
+];
+
+/// Boot phase durations in ms, as `systemd-analyze` reports them
+struct BootTimes {
+    firmware: u64,
+    loader: u64,
+    kernel: u64,
+    userspace: u64,
+}
+
+pub struct JournalStage {
+    host: Arc<HostProfile>,
+    kernel_logs: KernelLogs,
+}
+
+impl JournalStage {
+    pub fn new(host: Arc<HostProfile>) -> Self {
+        Self {
+            host,
+            kernel_logs: KernelLogs::load(),
+        }
+    }
+
+    fn seconds(ms: u64) -> String {
+        if ms >= 1000 {
+            format!("{:.3}s", ms as f64 / 1000.0)
+        } else {
+            format!("{}ms", ms)
+        }
+    }
+
+    fn analyze(&self, rng: &mut rand::rngs::ThreadRng) -> (BootTimes, Vec<(&'static str, u64)>) {
+        let firmware = rng.gen_range(2000..9000);
+        let loader = rng.gen_range(500..4000);
+        let kernel = rng.gen_range(1200..3500);
+        let userspace = rng.gen_range(4000..15000);
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "$ systemd-analyze".bright_white()
+        );
+        println!(
+            "Startup finished in {} (firmware) + {} (loader) + {} (kernel) + {} (userspace) = {}",
+            Self::seconds(firmware),
+            Self::seconds(loader),
+            Self::seconds(kernel),
+            Self::seconds(userspace),
+            Self::seconds(firmware + loader + kernel + userspace).bright_white()
+        );
+        println!(
+            "graphical.target reached after {} in userspace.",
+            Self::seconds(userspace)
+        );
+        println!();
+
+        let mut blame: Vec<(&'static str, u64)> = UNITS
+            .iter()
+            .map(|(unit, ..)| {
+                let ms = if *unit == "NetworkManager-wait-online.service" {
+                    rng.gen_range(3000..8000)
+                } else {
+                    rng.gen_range(15..2500)
+                };
+                (*unit, ms)
+            })
+            .collect();
+        blame.sort_by_key(|(_, ms)| std::cmp::Reverse(*ms));
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "$ systemd-analyze blame".bright_white()
+        );
+        for (unit, ms) in &blame {
+            let time = format!("{:>9}", Self::seconds(*ms));
+            let time = if *ms > 2000 {
+                time.bright_red()
+            } else if *ms > 800 {
+                time.yellow()
+            } else {
+                time.normal()
+            };
+            println!("{} {}", time, unit);
+            thread::sleep(Duration::from_millis(60));
+        }
+        println!();
+        let times = BootTimes {
+            firmware,
+            loader,
+            kernel,
+            userspace,
+        };
+        (times, blame)
+    }
+
+    fn critical_chain(&self, blame: &[(&'static str, u64)], rng: &mut rand::rngs::ThreadRng) {
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "$ systemd-analyze critical-chain".bright_white()
+        );
+        println!(
+            "{}",
+            "The time when unit became active or started is printed after the \"@\" character."
+                .dimmed()
+        );
+        println!(
+            "{}",
+            "The time the unit took to start is printed after the \"+\" character.".dimmed()
+        );
+        println!();
+
+        // Walk from the root of the chain outwards to get activation times,
+        // then print innermost-last as systemd does
+        let mut at = rng.gen_range(800..1200);
+        let mut entries = vec![("basic.target".to_string(), at, None)];
+        for unit in CRITICAL_CHAIN {
+            let took = blame
+                .iter()
+                .find(|(name, _)| name == unit)
+                .map(|(_, ms)| *ms)
+                .unwrap_or(100);
+            at += rng.gen_range(5..40);
+            entries.push((unit.to_string(), at, Some(took)));
+            at += took;
+        }
+        entries.push(("multi-user.target".to_string(), at + 3, None));
+        entries.push(("graphical.target".to_string(), at + 5, None));
+
+        for (depth, (unit, at, took)) in entries.iter().rev().enumerate() {
+            let branch = if depth == 0 {
+                String::new()
+            } else {
+                format!("{}└─", "  ".repeat(depth - 1))
+            };
+            let timing = match took {
+                Some(took) => format!("@{} +{}", Self::seconds(*at), Self::seconds(*took)),
+                None => format!("@{}", Self::seconds(*at)),
+            };
+            let timing = if took.is_some_and(|t| t > 2000) {
+                timing.bright_red()
+            } else {
+                timing.normal()
+            };
+            println!("{}{} {}", branch.dimmed(), unit.bright_white(), timing);
+        }
+        println!();
+    }
+
+    fn journal_line(&self, time: DateTime<Local>, source: &str, message: &str, priority: u8) {
+        let prefix = format!(
+            "{} {} {}:",
+            time.format("%b %d %H:%M:%S"),
+            self.host.hostname,
+            source
+        );
+        let message = match priority {
+            0..=3 => message.bright_red().bold(),
+            4 => message.yellow(),
+            5 => message.bright_white(),
+            6 => message.normal(),
+            _ => message.dimmed(),
+        };
+        println!("{} {}", prefix.dimmed(), message);
+    }
+
+    /// Print the kernel lines logged up to `until` that haven't been shown.
+    fn replay_kernel(
+        &self,
+        kernel: &[(DateTime<Local>, &str, u8)],
+        next: &mut usize,
+        until: DateTime<Local>,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        while let Some((time, line, priority)) = kernel.get(*next) {
+            if *time > until {
+                break;
+            }
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            self.journal_line(*time, "kernel", line, *priority);
+            *next += 1;
+            thread::sleep(Duration::from_millis(rng.gen_range(20..70)));
+        }
+        Ok(())
+    }
+}
+
+impl InstallationStage for JournalStage {
+    fn name(&self) -> &'static str {
+        "Boot Analysis and Journal"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+
+        let (times, blame) = self.analyze(&mut rng);
+        self.critical_chain(&blame, &mut rng);
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "$ journalctl -b --no-pager".bright_white()
+        );
+        spinner.animate("Reading journal files...", 800, exit_check)?;
+
+        let mut time = Local::now() - ChronoDuration::seconds(rng.gen_range(60..600));
+        println!(
+            "{}",
+            format!(
+                "-- Logs begin at {}, end at {}. --",
+                time.format("%a %Y-%m-%d %H:%M:%S %Z"),
+                Local::now().format("%a %Y-%m-%d %H:%M:%S %Z")
+            )
+            .dimmed()
+        );
+
+        // Kernel lines get their own timestamps across the same window as
+        // the units, and are replayed as the unit timeline passes them
+        let kernel = self.kernel_logs.all_logs();
+        let count = rng.gen_range(12..24).min(kernel.len());
+        let start = rng.gen_range(0..=kernel.len() - count);
+        let mut kernel_time = time;
+        let kernel: Vec<(DateTime<Local>, &str, u8)> = kernel[start..start + count]
+            .iter()
+            .map(|line| {
+                let at = kernel_time;
+                kernel_time += ChronoDuration::milliseconds(rng.gen_range(20..250));
+                let priority = if line.contains("error") || line.contains("fail") {
+                    3
+                } else if line.contains("warn") {
+                    4
+                } else {
+                    6
+                };
+                (at, line.as_str(), priority)
+            })
+            .collect();
+        let mut next_kernel = 0;
+
+        let (flaky, error) = *FLAKY_UNITS.choose(&mut rng).unwrap();
+        let fails = rng.gen_bool(0.6);
+        let mut pid = rng.gen_range(300..900);
+        for (unit, description, process, message) in UNITS {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            pid += rng.gen_range(1..60);
+            self.replay_kernel(&kernel, &mut next_kernel, time, &mut rng, exit_check)?;
+            self.journal_line(
+                time,
+                "systemd[1]",
+                &format!("Starting {}...", description),
+                6,
+            );
+            time += ChronoDuration::milliseconds(rng.gen_range(5..400));
+
+            if *unit == flaky && fails {
+                let proc = format!("{}[{}]", process, pid);
+                self.journal_line(time, &proc, error, 3);
+                self.journal_line(
+                    time,
+                    "systemd[1]",
+                    &format!(
+                        "{}: Main process exited, code=exited, status=1/FAILURE",
+                        unit
+                    ),
+                    3,
+                );
+                self.journal_line(
+                    time,
+                    "systemd[1]",
+                    &format!("{}: Failed with result 'exit-code'.", unit),
+                    4,
+                );
+                self.journal_line(
+                    time,
+                    "systemd[1]",
+                    &format!("Failed to start {}.", description),
+                    3,
+                );
+                thread::sleep(Duration::from_millis(1200));
+                time += ChronoDuration::seconds(2);
+                self.replay_kernel(&kernel, &mut next_kernel, time, &mut rng, exit_check)?;
+                self.journal_line(
+                    time,
+                    "systemd[1]",
+                    &format!("{}: Scheduled restart job, restart counter is at 1.", unit),
+                    5,
+                );
+                self.journal_line(
+                    time,
+                    "systemd[1]",
+                    &format!("Starting {}...", description),
+                    6,
+                );
+                pid += rng.gen_range(100..400);
+                time += ChronoDuration::milliseconds(rng.gen_range(200..900));
+            }
+
+            self.journal_line(time, &format!("{}[{}]", process, pid), message, 6);
+            self.journal_line(time, "systemd[1]", &format!("Started {}.", description), 5);
+            time += ChronoDuration::milliseconds(rng.gen_range(1..50));
+            thread::sleep(Duration::from_millis(rng.gen_range(80..220)));
+        }
+
+        if let Some((last, ..)) = kernel.last() {
+            time = time.max(*last);
+        }
+        self.replay_kernel(&kernel, &mut next_kernel, time, &mut rng, exit_check)?;
+        self.journal_line(time, "systemd[1]", "Reached target Multi-User System.", 6);
+        self.journal_line(time, "systemd[1]", "Reached target Graphical Interface.", 6);
+        self.journal_line(
+            time,
+            "systemd[1]",
+            &format!(
+                "Startup finished in {} (firmware) + {} (loader) + {} (kernel) + {} (userspace) = {}.",
+                Self::seconds(times.firmware),
+                Self::seconds(times.loader),
+                Self::seconds(times.kernel),
+                Self::seconds(times.userspace),
+                Self::seconds(times.firmware + times.loader + times.kernel + times.userspace)
+            ),
+            6,
+        );
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
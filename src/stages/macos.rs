@@ -0,0 +1,249 @@
+use super::InstallationStage;
+use crate::config::MacosConfig;
+use crate::host_profile::HostProfile;
+use crate::ui::Spinner;
+use colored::*;
+use crossterm::{
+    cursor, execute,
+    terminal::{self, ClearType},
+};
+use rand::Rng;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const BAR_WIDTH: usize = 40;
+
+const KEXTS: &[&str] = &[
+    "com.apple.iokit.IOPCIFamily",
+    "com.apple.iokit.IOACPIFamily",
+    "com.apple.iokit.IOStorageFamily",
+    "com.apple.iokit.IONVMeFamily",
+    "com.apple.iokit.IOUSBHostFamily",
+    "com.apple.iokit.IOHIDFamily",
+    "com.apple.iokit.IOGraphicsFamily",
+    "com.apple.iokit.IONetworkingFamily",
+    "com.apple.driver.AppleACPIPlatform",
+    "com.apple.driver.AppleSMC",
+    "com.apple.driver.AppleRTC",
+    "com.apple.filesystems.apfs",
+];
+
+pub struct MacosStage {
+    config: MacosConfig,
+    host: Arc<HostProfile>,
+}
+
+impl MacosStage {
+    pub fn new(config: MacosConfig, host: Arc<HostProfile>) -> Self {
+        Self { config, host }
+    }
+
+    /// Kernel lines as printed by a verbose (`-v`) boot.
+    fn verbose_boot_lines(&self, rng: &mut rand::rngs::ThreadRng) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "Darwin Kernel Version 24.{}.0: root:xnu-11215.{}.{}~1/RELEASE_X86_64",
+                rng.gen_range(0..4),
+                rng.gen_range(1..80),
+                rng.gen_range(1..20)
+            ),
+            format!(
+                "vm_page_bootstrap: {} free pages, {} wired pages",
+                self.host.total_memory_kb / 4 * rng.gen_range(80..95) / 100,
+                rng.gen_range(20000..60000)
+            ),
+            "standard timeslicing quantum is 10000 us".to_string(),
+            "mig_table_max_displ = 53".to_string(),
+            "IOAPIC: Version 0x20 Vectors 64:87".to_string(),
+            "ACPI: sleep states S3 S4 S5".to_string(),
+        ];
+
+        for cpu in 0..self.host.cpu_count.min(8) {
+            lines.push(format!(
+                "AppleACPICPU: ProcessorId={} LocalApicId={} Enabled",
+                cpu + 1,
+                cpu * 2
+            ));
+        }
+
+        lines.push("calling mpo_policy_init for TMSafetyNet".to_string());
+        lines.push("Security policy loaded: Safety net for Time Machine (TMSafetyNet)".to_string());
+        lines.push("calling mpo_policy_init for Sandbox".to_string());
+        lines.push("Security policy loaded: Seatbelt sandbox policy (Sandbox)".to_string());
+        lines.push("Copyright (c) 1982, 1986, 1989, 1991, 1993".to_string());
+        lines.push("The Regents of the University of California. All rights reserved.".to_string());
+
+        for kext in KEXTS {
+            lines.push(format!("IOKit: Loaded kext {}", kext));
+        }
+
+        lines.push(format!(
+            "AppleIntelCPUPowerManagement: Turbo Ratios {}",
+            rng.gen_range(3000..9999)
+        ));
+        lines.push("IOGraphics flags 0x3".to_string());
+        lines.push("com.apple.AppleFSCompressionTypeZlib kmod start".to_string());
+        lines.push(format!(
+            "BSD root: {}, major 1, minor 2",
+            self.host
+                .boot_disk()
+                .partition(2)
+                .trim_start_matches("/dev/")
+        ));
+        lines.push("apfs_vfsop_mountroot:2163: apfs: mountroot called!".to_string());
+        lines.push("hfs: mounted Install macOS on device root_device".to_string());
+        lines
+    }
+
+    /// Apple never shows percentages, only a rough and unreliable estimate.
+    fn describe_remaining(minutes: u64) -> String {
+        match minutes {
+            0 => "Less than a minute remaining".to_string(),
+            1 => "About a minute remaining".to_string(),
+            2..=59 => format!("About {} minutes remaining", minutes),
+            60..=119 => match minutes - 60 {
+                0 => "About 1 hour remaining".to_string(),
+                extra => format!("About 1 hour and {} minutes remaining", extra),
+            },
+            _ => format!("About {} hours remaining", minutes / 60),
+        }
+    }
+
+    /// Draw the bar on the current line and the caption on the line below,
+    /// leaving the cursor on the bar line for the next redraw.
+    fn draw(progress: f32, caption: &str) -> io::Result<()> {
+        let cols = terminal::size().map(|(c, _)| c as usize).unwrap_or(80);
+        let filled = ((progress * BAR_WIDTH as f32) as usize).min(BAR_WIDTH);
+        let bar = format!(
+            "{}{}",
+            "━".repeat(filled).bright_white(),
+            "━".repeat(BAR_WIDTH - filled).dimmed()
+        );
+        let bar_pad = " ".repeat(cols.saturating_sub(BAR_WIDTH) / 2);
+        let caption_pad = " ".repeat(cols.saturating_sub(caption.chars().count()) / 2);
+
+        let mut stdout = io::stdout();
+        execute!(
+            stdout,
+            cursor::MoveToColumn(0),
+            terminal::Clear(ClearType::CurrentLine)
+        )?;
+        print!("{}{}", bar_pad, bar);
+        execute!(
+            stdout,
+            cursor::MoveDown(1),
+            cursor::MoveToColumn(0),
+            terminal::Clear(ClearType::CurrentLine)
+        )?;
+        print!("{}{}", caption_pad, caption.dimmed());
+        execute!(stdout, cursor::MoveUp(1))?;
+        stdout.flush()
+    }
+
+    fn centered(text: &str) -> String {
+        let cols = terminal::size().map(|(c, _)| c as usize).unwrap_or(80);
+        format!(
+            "{}{}",
+            " ".repeat(cols.saturating_sub(text.chars().count()) / 2),
+            text
+        )
+    }
+
+    /// Run a centered progress bar whose caption is produced from the
+    /// current progress.
+    fn progress_phase(
+        &self,
+        duration_ms: u64,
+        exit_check: &dyn Fn() -> bool,
+        mut caption: impl FnMut(f32) -> String,
+    ) -> io::Result<()> {
+        // Reserve the caption line so MoveDown has somewhere to go
+        println!();
+        execute!(io::stdout(), cursor::MoveUp(1))?;
+
+        let steps = 100;
+        let delay = duration_ms / steps;
+        for i in 0..=steps {
+            if exit_check() {
+                println!("\n");
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            let progress = i as f32 / steps as f32;
+            Self::draw(progress, &caption(progress))?;
+            thread::sleep(Duration::from_millis(delay));
+        }
+        println!("\n");
+        Ok(())
+    }
+}
+
+impl InstallationStage for MacosStage {
+    fn name(&self) -> &'static str {
+        "macOS Installation"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+
+        for line in self.verbose_boot_lines(&mut rng) {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            println!("{}", line.dimmed());
+            thread::sleep(Duration::from_millis(
+                rng.gen_range(self.config.verbose_line_delay_range.clone()),
+            ));
+        }
+
+        println!();
+        println!(
+            "{}",
+            Self::centered(&format!("Installing {}", self.config.release))
+                .bright_white()
+                .bold()
+        );
+        println!();
+
+        self.progress_phase(self.config.prepare_time, exit_check, |_| {
+            "Preparing to install...".to_string()
+        })?;
+
+        // The estimate trends down with progress but wanders and occasionally
+        // jumps up by a lot, as it traditionally does
+        let total = rng.gen_range(self.config.estimate_minutes_range.clone()) as f32;
+        let mut drift: f32 = 0.0;
+        let mut shown = total as u64;
+        let mut ticks = 0;
+        let spike_chance = self.config.estimate_spike_chance;
+        self.progress_phase(self.config.install_time, exit_check, |progress| {
+            ticks += 1;
+            if ticks % 8 == 0 {
+                let mut rng = rand::thread_rng();
+                drift = (drift + rng.gen_range(-3.0..3.0)).clamp(-total / 4.0, total);
+                if rng.gen_bool(spike_chance) {
+                    drift += rng.gen_range(10.0..60.0);
+                }
+                shown = ((1.0 - progress) * total + drift * (1.0 - progress)).max(0.0) as u64;
+            }
+            if progress >= 1.0 {
+                shown = 0;
+            }
+            Self::describe_remaining(shown)
+        })?;
+
+        let mut spinner = Spinner::new();
+        spinner.animate("Setting up your Mac...", self.config.setup_time, exit_check)?;
+        println!(
+            "{}",
+            Self::centered("Your Mac will restart to complete the installation.").dimmed()
+        );
+        thread::sleep(Duration::from_millis(800));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,357 @@
+use super::InstallationStage;
+use crate::log_generator::LogGenerator;
+use crate::ui::Spinner;
+use chrono::Local;
+use colored::*;
+use rand::Rng;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Console messages during nucleus initialisation, up to the first WTOR
+const IPL_MESSAGES: &[&str] = &[
+    "IEA371I SYS1.IPLPARM ON DEVICE 0A82 SELECTED FOR IPL PARAMETERS",
+    "IEA246I LOAD   ID 00 SELECTED",
+    "IEA246I NUCLST ID 00 SELECTED",
+    "IEA519I IODF DSN = SYS1.IODF00",
+    "IEA520I CONFIGURATION ID = ZOS31 . IODF DEVICE NUMBER = 0A82",
+    "IEA091I NUCLEUS 1 SELECTED",
+    "IEA370I MASTER CATALOG SELECTED IS CATALOG.Z31.MASTER",
+    "IEE252I MEMBER IEASYS00 FOUND IN SYS1.PARMLIB",
+    "IAR013I 4,096M STORAGE IS RECONFIGURABLE",
+    "IEA008I SYSTEM PARMS FOLLOW FOR z/OS 03.01.00 HBB77E0",
+    "IEE252I MEMBER LPALST00 FOUND IN SYS1.PARMLIB",
+    "IEE252I MEMBER PROG00 FOUND IN SYS1.PARMLIB",
+    "IEE252I MEMBER CONSOL00 FOUND IN SYS1.PARMLIB",
+];
+
+/// Console messages after the CLPA reply while the system comes up
+const SYSTEM_MESSAGES: &[&str] = &[
+    "IEA630I OPERATOR *MASTER* NOW ACTIVE,   SYSTEM=SYS1    , LU=HMCS",
+    "IEE712I SET IOS PROCESSING COMPLETE",
+    "IXC418I SYSTEM SYS1 IS NOW ACTIVE IN SYSPLEX LOCAL",
+    "IEF196I IEF237I 0A82 ALLOCATED TO SYS00001",
+    "IEE252I MEMBER COMMND00 FOUND IN SYS1.PARMLIB",
+    "IEE536I SMF      VALUE SYS1 NOW IN EFFECT",
+    "IWM050I STORAGE MANAGEMENT SUBSYSTEM ACTIVE",
+    "IGW061I SMSPDSE INITIALIZATION COMPLETE.",
+    "IEF677I WARNING MESSAGE(S) FOR JOB JES2     ISSUED",
+];
+
+const JES2_MESSAGES: &[&str] = &[
+    "$HASP493 JES2 COLD START IS IN PROGRESS - z/OS 3.1 SSNAME=JES2",
+    "$HASP537 THE CURRENT CHECKPOINT USES 4 4K RECORDS",
+    "$HASP436 CONFIRM COLD START ON CKPT1 - VOLSER=JES2C1 DSN=SYS1.HASPCKPT",
+    "$HASP160 PRINTER1 INACTIVE - CLASS=A",
+    "$HASP160 PUNCH1   INACTIVE - CLASS=B",
+    "$HASP492 JES2 COLD START HAS COMPLETED",
+    "IEE612I CN=01      DEVNUM=0700 SYS=SYS1",
+    "TCP/IP: EZZ4202I Z/OS UNIX - TCP/IP CONNECTION ESTABLISHED FOR TCPIP",
+    "IST020I VTAM INITIALIZATION COMPLETE FOR CSV3R1",
+    "IKJ56455I TSO/E INITIALIZATION COMPLETE",
+];
+
+/// (step, program, description, condition code)
+const JOB_STEPS: &[(&str, &str, &str, u32)] = &[
+    ("ALLOC", "IEFBR14", "allocate target datasets", 0),
+    ("UNLOAD", "IEBCOPY", "copy load modules", 0),
+    ("LINK", "IEWL", "link-edit CURSED", 4),
+    ("APF", "IKJEFT01", "authorize load library", 0),
+];
+
+const JCL: &[&str] = &[
+    "//INSTALL  JOB (ACCT),'CURSED INSTALL',CLASS=A,MSGCLASS=X,",
+    "//             NOTIFY=&SYSUID,REGION=0M",
+    "//*",
+    "//ALLOC    EXEC PGM=IEFBR14",
+    "//LOADLIB  DD DSN=CURSED.V1R0.SCURLOAD,DISP=(NEW,CATLG),",
+    "//            SPACE=(CYL,(50,10,100)),DSNTYPE=LIBRARY,",
+    "//            RECFM=U,BLKSIZE=32760,UNIT=SYSALLDA",
+    "//UNLOAD   EXEC PGM=IEBCOPY",
+    "//SYSPRINT DD SYSOUT=*",
+    "//IN       DD DSN=CURSED.V1R0.DIST,DISP=SHR",
+    "//OUT      DD DSN=CURSED.V1R0.SCURLOAD,DISP=OLD",
+    "//SYSIN    DD *",
+    "  COPY OUTDD=OUT,INDD=IN",
+    "/*",
+    "//LINK     EXEC PGM=IEWL,PARM='LIST,XREF,RENT,AC=1'",
+    "//SYSLMOD  DD DSN=CURSED.V1R0.SCURLOAD(CURSED),DISP=SHR",
+    "//SYSLIN   DD DSN=CURSED.V1R0.OBJ(CURSED),DISP=SHR",
+    "//SYSPRINT DD SYSOUT=*",
+    "//APF      EXEC PGM=IKJEFT01",
+    "//SYSTSPRT DD SYSOUT=*",
+    "//SYSTSIN  DD *",
+    "  SETPROG APF,ADD,DSNAME=CURSED.V1R0.SCURLOAD,SMS",
+    "/*",
+];
+
+/// Width of the 3270 model 2 screen
+const SCREEN_WIDTH: usize = 80;
+
+pub struct MainframeStage;
+
+impl MainframeStage {
+    /// A line on the master console, prefixed with the MVS clock.
+    fn console(message: &str) {
+        println!(
+            "{} {}",
+            Local::now().format("%H.%M.%S").to_string().green(),
+            message.bright_green()
+        );
+    }
+
+    fn console_batch(
+        messages: &[&str],
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        for message in messages {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            Self::console(message);
+            thread::sleep(Duration::from_millis(rng.gen_range(120..450)));
+        }
+        Ok(())
+    }
+
+    /// The operator typing a reply to an outstanding WTOR.
+    fn reply(id: u32, text: &str, rng: &mut rand::rngs::ThreadRng) -> io::Result<()> {
+        thread::sleep(Duration::from_millis(800));
+        print!("{} ", "==>".bright_white());
+        io::stdout().flush()?;
+        for c in format!("R {:02},{}", id, text).chars() {
+            print!("{}", c.to_string().bright_white());
+            io::stdout().flush()?;
+            thread::sleep(Duration::from_millis(rng.gen_range(60..180)));
+        }
+        println!();
+        Self::console(&format!("IEE600I REPLY TO {:02} IS;{}", id, text));
+        Ok(())
+    }
+
+    /// Pad a line to the screen width inside the 3270 border.
+    fn screen_row(text: &str) -> String {
+        format!(
+            "{}{:<width$}{}",
+            "│".green(),
+            text,
+            "│".green(),
+            width = SCREEN_WIDTH - 2
+        )
+    }
+
+    fn logon_screen() {
+        let rule = "─".repeat(SCREEN_WIDTH - 2);
+        println!("{}", format!("┌{}┐", rule).green());
+        let rows = [
+            String::new(),
+            format!("{:^78}", "z/OS Z31 Level 2309"),
+            String::new(),
+            format!("{:^78}", "IP Address = 10.1.1.2"),
+            format!("{:^78}", "VTAM Terminal = SC0TCP01"),
+            String::new(),
+            format!("{:^78}", "Application Developer System"),
+            String::new(),
+            format!("{:^78}", "Enter your userid, or LOGON applid userid:"),
+            String::new(),
+            format!("{:^78}", "System Customization - ADCD.Z31.*"),
+            String::new(),
+        ];
+        for row in &rows {
+            println!("{}", Self::screen_row(row).bright_green());
+            thread::sleep(Duration::from_millis(40));
+        }
+        println!(
+            "{}{}{}",
+            "│".green(),
+            format!("{:<78}", " ===> LOGON IBMUSER")
+                .bright_white()
+                .bold(),
+            "│".green()
+        );
+        println!(
+            "{}",
+            Self::screen_row(&format!("{:>77}", "RUNNING  SC0TCP01")).green()
+        );
+        println!("{}", format!("└{}┘", rule).green());
+    }
+
+    /// The step-end statistics JES2 writes to JESMSGLG.
+    fn step_line(step: &str, rc: &str, rng: &mut rand::rngs::ThreadRng) {
+        println!(
+            "{}",
+            format!(
+                "-INSTALL  {:<8}             {:>4} {:>6}    .00    .00    .{:02} {:>6}   0      0      0      0     0",
+                step,
+                rc,
+                rng.gen_range(20..900),
+                rng.gen_range(1..20),
+                rng.gen_range(200..4000)
+            )
+            .bright_green()
+        );
+    }
+
+    fn run_job(
+        rng: &mut rand::rngs::ThreadRng,
+        job: u32,
+        restart: Option<&str>,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<bool> {
+        Self::console(&format!(
+            "$HASP100 INSTALL  ON INTRDR                     FROM TSU{:05} IBMUSER",
+            job - 1
+        ));
+        Self::console("IRR010I  USERID IBMUSER  IS ASSIGNED TO THIS JOB.");
+        Self::console("$HASP373 INSTALL  STARTED - INIT 1    - CLASS A        - SYS SYS1");
+        Self::console(&format!(
+            "IEF403I INSTALL - STARTED - TIME={}",
+            Local::now().format("%H.%M.%S")
+        ));
+        println!(
+            "{}",
+            "-                                         --TIMINGS (MINS.)--            ----PAGING COUNTS---"
+                .green()
+        );
+        println!(
+            "{}",
+            "-JOBNAME  STEPNAME PROCSTEP    RC   EXCP    CPU    SRB  CLOCK   SERV  PG   PAGE   SWAP    VIO SWAPS"
+                .green()
+        );
+
+        let mut spinner = Spinner::new();
+        let mut max_rc = 0;
+        let first = restart
+            .and_then(|step| JOB_STEPS.iter().position(|(name, ..)| *name == step))
+            .unwrap_or(0);
+        for (step, program, description, rc) in &JOB_STEPS[first..] {
+            spinner.animate(
+                &format!("{} {} ({})", step, program, description),
+                rng.gen_range(900..2200),
+                exit_check,
+            )?;
+
+            // The link-edit is where an unlucky install abends
+            if restart.is_none() && *step == "LINK" && rng.gen_bool(0.3) {
+                let (code, reason) = [
+                    ("0C4", "PROTECTION EXCEPTION"),
+                    ("806", "MODULE NOT FOUND"),
+                    ("B37", "END OF VOLUME, NO SPACE"),
+                ][rng.gen_range(0..3)];
+                Self::console(&format!(
+                    "IEA995I SYMPTOM DUMP OUTPUT  {} SYSTEM COMPLETION CODE={}  REASON CODE=00000004",
+                    reason, code
+                ));
+                Self::console(&format!(
+                    " TIME={}  SEQ=0{}  CPU=0000  ASID=002{}",
+                    Local::now().format("%H.%M.%S"),
+                    rng.gen_range(1000..9999),
+                    rng.gen_range(1..9)
+                ));
+                Self::console(&format!(
+                    " PSW AT TIME OF ERROR  078D1000   {}",
+                    &LogGenerator::hex_addr()[10..]
+                ));
+                Self::step_line(step, &format!("*S{}", code), rng);
+                println!(
+                    "{} {}",
+                    Local::now().format("%H.%M.%S").to_string().green(),
+                    format!("$HASP395 INSTALL  ENDED - ABEND=S{}", code).bright_red()
+                );
+                return Ok(false);
+            }
+
+            Self::step_line(step, &format!("{:02}", rc), rng);
+            max_rc = max_rc.max(*rc);
+        }
+        Self::console(&format!(
+            "IEF404I INSTALL - ENDED - TIME={}",
+            Local::now().format("%H.%M.%S")
+        ));
+        Self::console(&format!("$HASP395 INSTALL  ENDED - RC={:04}", max_rc));
+        Ok(true)
+    }
+}
+
+impl InstallationStage for MainframeStage {
+    fn name(&self) -> &'static str {
+        "Mainframe IPL and Batch Install"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "HMC: LOAD  Load address 0A80  Load parameter 0A8200M1".bright_white()
+        );
+        spinner.animate("Performing IPL of image ZOS31...", 2500, exit_check)?;
+        println!();
+
+        Self::console_batch(IPL_MESSAGES, &mut rng, exit_check)?;
+        Self::console("*00 IEA101A SPECIFY SYSTEM PARAMETERS FOR z/OS 03.01.00 HBB77E0");
+        Self::reply(0, "CLPA", &mut rng)?;
+        Self::console_batch(SYSTEM_MESSAGES, &mut rng, exit_check)?;
+
+        Self::console("*01 $HASP426 SPECIFY OPTIONS - JES2 z/OS 3.1 SSNAME=JES2");
+        Self::reply(1, "COLD,NOREQ", &mut rng)?;
+        Self::console_batch(JES2_MESSAGES, &mut rng, exit_check)?;
+        println!();
+
+        Self::logon_screen();
+        thread::sleep(Duration::from_millis(1200));
+        println!();
+
+        println!(
+            "{}",
+            " EDIT       IBMUSER.JCL(INSTALL) - 01.00                  Columns 00001 00072"
+                .black()
+                .on_green()
+        );
+        for (i, line) in JCL.iter().enumerate() {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            println!(
+                "{} {}",
+                format!("{:06}", (i + 1) * 100).green(),
+                line.bright_green()
+            );
+            thread::sleep(Duration::from_millis(50));
+        }
+        println!("{}", " Command ===> SUBMIT".bright_white());
+        let job = rng.gen_range(4000..9000);
+        println!(
+            "{}",
+            format!(" IKJ56250I JOB INSTALL(JOB{:05}) SUBMITTED", job).bright_green()
+        );
+        println!();
+        thread::sleep(Duration::from_millis(800));
+
+        if !Self::run_job(&mut rng, job, None, exit_check)? {
+            println!();
+            thread::sleep(Duration::from_millis(1500));
+            // Restart from the failed step rather than reallocating
+            println!(
+                "{} {}",
+                format!("{:06}", 200).green(),
+                "//             NOTIFY=&SYSUID,REGION=0M,RESTART=LINK".bright_white()
+            );
+            println!("{}", " Command ===> SUBMIT".bright_white());
+            println!(
+                "{}",
+                format!(" IKJ56250I JOB INSTALL(JOB{:05}) SUBMITTED", job + 1).bright_green()
+            );
+            Self::run_job(&mut rng, job + 1, Some("LINK"), exit_check)?;
+        }
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
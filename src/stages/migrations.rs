@@ -0,0 +1,294 @@
+use super::database::DatabaseEngine;
+use super::InstallationStage;
+use crate::log_generator::LogGenerator;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use chrono::{Duration as ChronoDuration, Local};
+use colored::*;
+use rand::Rng;
+use std::io;
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+/// (name, PostgreSQL statement, MySQL statement)
+const MIGRATIONS: &[(&str, &str, &str)] = &[
+    (
+        "create_users",
+        "CREATE TABLE users (id BIGSERIAL PRIMARY KEY, email TEXT NOT NULL, created_at TIMESTAMPTZ NOT NULL DEFAULT now());",
+        "CREATE TABLE users (id BIGINT AUTO_INCREMENT PRIMARY KEY, email VARCHAR(255) NOT NULL, created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP);",
+    ),
+    (
+        "create_sessions",
+        "CREATE TABLE sessions (token UUID PRIMARY KEY, user_id BIGINT REFERENCES users (id) ON DELETE CASCADE, expires_at TIMESTAMPTZ);",
+        "CREATE TABLE sessions (token CHAR(36) PRIMARY KEY, user_id BIGINT, expires_at DATETIME, FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE);",
+    ),
+    (
+        "add_display_name_to_users",
+        "ALTER TABLE users ADD COLUMN display_name TEXT;",
+        "ALTER TABLE users ADD COLUMN display_name VARCHAR(100), ALGORITHM=INSTANT;",
+    ),
+    (
+        "create_events",
+        "CREATE TABLE events (id BIGSERIAL PRIMARY KEY, user_id BIGINT, kind TEXT NOT NULL, payload JSONB, created_at TIMESTAMPTZ NOT NULL);",
+        "CREATE TABLE events (id BIGINT AUTO_INCREMENT PRIMARY KEY, user_id BIGINT, kind VARCHAR(64) NOT NULL, payload JSON, created_at DATETIME NOT NULL);",
+    ),
+    (
+        "add_unique_email_index",
+        "CREATE UNIQUE INDEX idx_users_email ON users (lower(email));",
+        "ALTER TABLE users ADD UNIQUE INDEX idx_users_email (email);",
+    ),
+    (
+        "index_events_created_at",
+        "CREATE INDEX CONCURRENTLY idx_events_created_at ON events (created_at);",
+        "ALTER TABLE events ADD INDEX idx_events_created_at (created_at), ALGORITHM=INPLACE, LOCK=NONE;",
+    ),
+    (
+        "backfill_display_names",
+        "UPDATE users SET display_name = split_part(email, '@', 1) WHERE display_name IS NULL;",
+        "UPDATE users SET display_name = SUBSTRING_INDEX(email, '@', 1) WHERE display_name IS NULL;",
+    ),
+];
+
+/// The migration that may hit duplicate rows and roll back
+const FRAGILE_MIGRATION: &str = "add_unique_email_index";
+/// The migration that builds an index over a large table
+const INDEX_MIGRATION: &str = "index_events_created_at";
+
+pub struct MigrationsStage {
+    engine: Arc<OnceLock<DatabaseEngine>>,
+}
+
+impl MigrationsStage {
+    pub fn new(engine: Arc<OnceLock<DatabaseEngine>>) -> Self {
+        Self { engine }
+    }
+
+    fn lock_wait(
+        engine: DatabaseEngine,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let waits = rng.gen_range(1..4);
+        for i in 0..waits {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            let line = match engine {
+                DatabaseEngine::PostgreSql => format!(
+                    "LOG:  process {} still waiting for AccessExclusiveLock on relation {} of database 16384 after {}.{:03} ms",
+                    rng.gen_range(2000..9000),
+                    rng.gen_range(16400..16500),
+                    1000 * (i + 1),
+                    rng.gen_range(0..1000)
+                ),
+                DatabaseEngine::MySql => format!(
+                    "Warning: Waiting for table metadata lock (held by connection {}, {}s)",
+                    rng.gen_range(20..400),
+                    i + 1
+                ),
+            };
+            println!("{} {}", LogGenerator::timestamp().dimmed(), line.yellow());
+            thread::sleep(Duration::from_millis(1000));
+        }
+        Ok(())
+    }
+
+    /// The long index build, reported the way each server exposes progress.
+    fn build_index(
+        engine: DatabaseEngine,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let rows: u64 = rng.gen_range(20_000_000..90_000_000);
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("events: ~{} rows", rows).dimmed()
+        );
+        let phases: &[&str] = match engine {
+            DatabaseEngine::PostgreSql => &[
+                "building index: scanning table",
+                "building index: sorting live tuples",
+                "building index: loading tuples in tree",
+                "waiting for old snapshots",
+            ],
+            DatabaseEngine::MySql => &[
+                "stage/innodb/alter table (read PK and internal sort)",
+                "stage/innodb/alter table (merge sort)",
+                "stage/innodb/alter table (insert)",
+                "stage/innodb/alter table (flush)",
+            ],
+        };
+        let progress = ProgressBar::new(ProgressStyle::Equals);
+        for phase in phases {
+            progress.animate(
+                &format!("{:<52}", phase),
+                rng.gen_range(1500..4000),
+                exit_check,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn fail_and_roll_back(
+        engine: DatabaseEngine,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let email =
+            ["alice@example.com", "bob@example.com", "admin@example.com"][rng.gen_range(0..3)];
+        match engine {
+            DatabaseEngine::PostgreSql => {
+                println!(
+                    "{}",
+                    "ERROR:  could not create unique index \"idx_users_email\"".bright_red()
+                );
+                println!(
+                    "{}",
+                    format!("DETAIL:  Key (lower(email))=({}) is duplicated.", email).bright_red()
+                );
+            }
+            DatabaseEngine::MySql => {
+                println!(
+                    "{}",
+                    format!(
+                        "ERROR 1062 (23000): Duplicate entry '{}' for key 'users.idx_users_email'",
+                        email
+                    )
+                    .bright_red()
+                );
+            }
+        }
+        println!("{}", "ROLLBACK".bright_yellow());
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("Migration {} failed, schema restored", FRAGILE_MIGRATION).yellow()
+        );
+        thread::sleep(Duration::from_millis(1000));
+        if exit_check() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+        }
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "Running on_error hook: scripts/dedupe_emails.sql".dimmed()
+        );
+        println!("{}", format!("DELETE {}", rng.gen_range(2..40)).dimmed());
+        thread::sleep(Duration::from_millis(800));
+        Ok(())
+    }
+}
+
+impl InstallationStage for MigrationsStage {
+    fn name(&self) -> &'static str {
+        "Database Migrations"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+        let engine = DatabaseEngine::choose(&self.engine);
+        let (url, lock) = match engine {
+            DatabaseEngine::PostgreSql => (
+                "postgres://app@localhost:5432/app",
+                "SELECT pg_advisory_lock(72707369);",
+            ),
+            DatabaseEngine::MySql => (
+                "mysql://app@localhost:3306/app",
+                "SELECT GET_LOCK('schema_migrations', 60);",
+            ),
+        };
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("$ migrate -database {} -path db/migrations up", url).bright_white()
+        );
+        println!(
+            "{} {} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("Connected to {} {}", engine.name(), engine.version()).dimmed(),
+            lock.dimmed()
+        );
+        spinner.animate("Reading schema_migrations...", 1000, exit_check)?;
+        println!();
+
+        // Timestamped names spaced out over the last few months, close
+        // enough together that the newest still lands before today
+        let offset = rng.gen_range(90..200);
+        let spacing = offset / (MIGRATIONS.len() as i64 + 1);
+        let mut stamp = Local::now() - ChronoDuration::days(offset);
+        let mut applied = 0;
+        for (name, postgres, mysql) in MIGRATIONS {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            stamp += ChronoDuration::days(rng.gen_range(1..spacing))
+                + ChronoDuration::seconds(rng.gen_range(0..86_400));
+            let version = stamp.format("%Y%m%d%H%M%S").to_string();
+            let sql = match engine {
+                DatabaseEngine::PostgreSql => postgres,
+                DatabaseEngine::MySql => mysql,
+            };
+
+            let mut attempt = 1;
+            loop {
+                let retry = if attempt > 1 {
+                    format!(" (attempt {}/3)", attempt)
+                } else {
+                    String::new()
+                };
+                println!(
+                    "{} {}{}",
+                    "Applying".bright_cyan(),
+                    format!("{}_{}.up.sql", version, name).bright_white(),
+                    retry.yellow()
+                );
+                println!("  {}", sql.dimmed());
+
+                if sql.starts_with("ALTER") && rng.gen_bool(0.4) {
+                    Self::lock_wait(engine, &mut rng, exit_check)?;
+                }
+                if *name == INDEX_MIGRATION {
+                    Self::build_index(engine, &mut rng, exit_check)?;
+                } else {
+                    thread::sleep(Duration::from_millis(rng.gen_range(200..700)));
+                }
+
+                if *name == FRAGILE_MIGRATION && attempt == 1 && rng.gen_bool(0.3) {
+                    Self::fail_and_roll_back(engine, &mut rng, exit_check)?;
+                    attempt += 1;
+                    continue;
+                }
+                break;
+            }
+
+            println!(
+                "{} {} {}",
+                "  ✓".bright_green(),
+                format!("{}/{}", version, name).bright_green(),
+                format!("({}ms)", rng.gen_range(4..900)).dimmed()
+            );
+            applied += 1;
+        }
+
+        println!();
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!(
+                "{} migrations applied, schema at version {}",
+                applied,
+                stamp.format("%Y%m%d%H%M%S")
+            )
+            .bright_green()
+        );
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
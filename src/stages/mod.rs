@@ -1,46 +1,89 @@
 mod ai;
+mod antivirus;
 mod bios;
+mod blockchain;
 mod boot;
 mod bootloader;
+mod cargo;
+mod certificates;
 mod cloud;
 mod compilation;
 mod container;
 mod database;
 mod deno;
+mod dist_upgrade;
 mod drivers;
 mod filesystem;
+mod firmware;
+mod floppy;
+mod gentoo;
+mod home_computer;
 mod initramfs;
+mod journal;
 mod kernel;
 mod locale;
+mod macos;
+mod mainframe;
+mod migrations;
 mod network;
+mod npm;
 mod packages;
+mod partition;
+mod pipeline;
+mod python;
 mod retro;
 mod services;
+mod storage;
 mod system;
+mod training;
+mod windows;
 mod xorg;
 
 use crate::cli::Stage;
+use crate::host_profile::HostProfile;
+use crate::scanner::ScanResult;
 use std::io;
+use std::sync::{Arc, OnceLock};
 
 pub use ai::AiStage;
+pub use antivirus::AntivirusStage;
 pub use bios::BiosStage;
+pub use blockchain::BlockchainStage;
 pub use boot::BootStage;
 pub use bootloader::BootloaderStage;
+pub use cargo::CargoStage;
+pub use certificates::CertificatesStage;
 pub use cloud::CloudStage;
 pub use compilation::CompilationStage;
 pub use container::ContainerStage;
 pub use database::DatabaseStage;
 pub use deno::DenoStage;
+pub use dist_upgrade::DistUpgradeStage;
 pub use drivers::DriversStage;
 pub use filesystem::FilesystemStage;
+pub use firmware::FirmwareStage;
+pub use floppy::FloppyStage;
+pub use gentoo::GentooStage;
+pub use home_computer::HomeComputerStage;
 pub use initramfs::InitramfsStage;
+pub use journal::JournalStage;
 pub use kernel::KernelStage;
 pub use locale::LocaleStage;
+pub use macos::MacosStage;
+pub use mainframe::MainframeStage;
+pub use migrations::MigrationsStage;
 pub use network::NetworkStage;
+pub use npm::NpmStage;
 pub use packages::PackagesStage;
+pub use partition::PartitionStage;
+pub use pipeline::PipelineStage;
+pub use python::PythonStage;
 pub use retro::RetroSoftwareStage;
 pub use services::ServicesStage;
+pub use storage::StorageStage;
 pub use system::SystemStage;
+pub use training::TrainingStage;
+pub use windows::WindowsStage;
 pub use xorg::XorgStage;
 
 /// Common trait for all installation stages
@@ -52,32 +95,61 @@ pub trait InstallationStage {
 use crate::config::SimulationConfig;
 
 /// Get selected installation stages in order
-pub fn selected_stages(stages: &[Stage]) -> Vec<Box<dyn InstallationStage>> {
+pub fn selected_stages(
+    stages: &[Stage],
+    host: &Arc<HostProfile>,
+    scan: Option<&Arc<ScanResult>>,
+) -> Vec<Box<dyn InstallationStage>> {
     let mut result = Vec::new();
     let config = SimulationConfig::default();
+    // Filled by whichever database stage runs first this cycle
+    let database_engine = Arc::new(OnceLock::new());
 
     for stage in stages {
         let stage_impl: Box<dyn InstallationStage> = match stage {
-            Stage::Bios => Box::new(BiosStage::new(config.bios.clone())),
+            Stage::Bios => Box::new(BiosStage::new(config.bios.clone(), host.clone())),
             Stage::Boot => Box::new(BootStage::new(config.boot.clone())),
-            Stage::Bootloader => Box::new(BootloaderStage::new(config.bootloader.clone())),
-            Stage::Filesystem => Box::new(FilesystemStage),
+            Stage::Bootloader => Box::new(BootloaderStage::new(
+                config.bootloader.clone(),
+                host.clone(),
+            )),
+            Stage::Filesystem => Box::new(FilesystemStage::new(host.clone())),
             Stage::System => Box::new(SystemStage),
-            Stage::Network => Box::new(NetworkStage),
-            Stage::Drivers => Box::new(DriversStage),
+            Stage::Network => Box::new(NetworkStage::new(host.clone())),
+            Stage::Drivers => Box::new(DriversStage::new(host.clone())),
             Stage::Initramfs => Box::new(InitramfsStage),
             Stage::Packages => Box::new(PackagesStage),
             Stage::Kernel => Box::new(KernelStage::new()),
             Stage::Compilation => Box::new(CompilationStage::new()),
             Stage::Deno => Box::new(DenoStage::new()),
-            Stage::Database => Box::new(DatabaseStage),
-            Stage::Xorg => Box::new(XorgStage),
+            Stage::Database => Box::new(DatabaseStage::new(database_engine.clone())),
+            Stage::Xorg => Box::new(XorgStage::new(host.clone())),
             Stage::Services => Box::new(ServicesStage),
             Stage::Retro => Box::new(RetroSoftwareStage),
             Stage::Locale => Box::new(LocaleStage),
             Stage::Container => Box::new(ContainerStage::new(config.container.clone())),
             Stage::Ai => Box::new(AiStage::new(config.ai.clone())),
             Stage::Cloud => Box::new(CloudStage::new(config.cloud.clone())),
+            Stage::Windows => Box::new(WindowsStage::new(config.windows.clone(), host.clone())),
+            Stage::Macos => Box::new(MacosStage::new(config.macos.clone(), host.clone())),
+            Stage::Gentoo => Box::new(GentooStage::new(config.gentoo.clone())),
+            Stage::Cargo => Box::new(CargoStage),
+            Stage::Npm => Box::new(NpmStage::new(scan.cloned())),
+            Stage::Python => Box::new(PythonStage::new(config.python.clone())),
+            Stage::Firmware => Box::new(FirmwareStage::new(host.clone())),
+            Stage::Partition => Box::new(PartitionStage::new(host.clone())),
+            Stage::Storage => Box::new(StorageStage::new(config.storage.clone(), host.clone())),
+            Stage::Certificates => Box::new(CertificatesStage::new(host.clone())),
+            Stage::Pipeline => Box::new(PipelineStage),
+            Stage::Migrations => Box::new(MigrationsStage::new(database_engine.clone())),
+            Stage::Journal => Box::new(JournalStage::new(host.clone())),
+            Stage::Mainframe => Box::new(MainframeStage),
+            Stage::Floppy => Box::new(FloppyStage),
+            Stage::HomeComputer => Box::new(HomeComputerStage),
+            Stage::Blockchain => Box::new(BlockchainStage::new(config.blockchain.clone())),
+            Stage::Training => Box::new(TrainingStage::new(config.training.clone())),
+            Stage::Antivirus => Box::new(AntivirusStage::new(scan.cloned())),
+            Stage::DistUpgrade => Box::new(DistUpgradeStage::new(host.clone())),
         };
         result.push(stage_impl);
     }
@@ -1,13 +1,23 @@
 use super::InstallationStage;
+use crate::host_profile::HostProfile;
 use crate::log_generator::LogGenerator;
 use crate::ui::Spinner;
 use colored::*;
 use rand::Rng;
 use std::io;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-pub struct NetworkStage;
+pub struct NetworkStage {
+    host: Arc<HostProfile>,
+}
+
+impl NetworkStage {
+    pub fn new(host: Arc<HostProfile>) -> Self {
+        Self { host }
+    }
+}
 
 impl InstallationStage for NetworkStage {
     fn name(&self) -> &'static str {
@@ -28,8 +38,7 @@ impl InstallationStage for NetworkStage {
         );
         thread::sleep(Duration::from_millis(500));
 
-        let interfaces = ["eth0", "enp0s3", "wlan0"];
-        let interface = interfaces[rng.gen_range(0..interfaces.len())];
+        let interface = self.host.primary_interface();
 
         println!(
             "{} {}",
@@ -63,7 +72,7 @@ impl InstallationStage for NetworkStage {
         println!(
             "{} {}",
             LogGenerator::timestamp().dimmed(),
-            format!("  Netmask: 255.255.255.0").dimmed()
+            "  Netmask: 255.255.255.0".dimmed()
         );
         println!(
             "{} {}",
@@ -73,7 +82,7 @@ impl InstallationStage for NetworkStage {
         println!(
             "{} {}",
             LogGenerator::timestamp().dimmed(),
-            format!("  DNS: 8.8.8.8, 8.8.4.4").dimmed()
+            "  DNS: 8.8.8.8, 8.8.4.4".dimmed()
         );
         thread::sleep(Duration::from_millis(600));
 
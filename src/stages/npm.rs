@@ -0,0 +1,382 @@
+use super::InstallationStage;
+use crate::log_generator::LogGenerator;
+use crate::scanner::ScanResult;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use colored::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// (name, version)
+type Package = (&'static str, &'static str);
+
+/// Top-level dependencies and a few of their own, as `npm ls` would show them
+const DEPENDENCIES: &[(&str, &str, &[Package])] = &[
+    ("react", "18.3.1", &[("loose-envify", "1.4.0")]),
+    (
+        "react-dom",
+        "18.3.1",
+        &[("scheduler", "0.23.2"), ("loose-envify", "1.4.0")],
+    ),
+    ("lodash", "4.17.21", &[]),
+    (
+        "express",
+        "4.19.2",
+        &[
+            ("body-parser", "1.20.2"),
+            ("cookie", "0.6.0"),
+            ("path-to-regexp", "0.1.7"),
+        ],
+    ),
+    (
+        "webpack",
+        "5.93.0",
+        &[
+            ("acorn", "8.12.1"),
+            ("enhanced-resolve", "5.17.0"),
+            ("terser-webpack-plugin", "5.3.10"),
+        ],
+    ),
+    ("typescript", "5.5.4", &[]),
+    (
+        "axios",
+        "1.7.2",
+        &[("follow-redirects", "1.15.6"), ("form-data", "4.0.0")],
+    ),
+    (
+        "eslint",
+        "8.57.0",
+        &[("espree", "9.6.1"), ("glob", "7.2.3")],
+    ),
+    (
+        "jest",
+        "29.7.0",
+        &[("@jest/core", "29.7.0"), ("jest-cli", "29.7.0")],
+    ),
+    ("left-pad", "1.3.0", &[]),
+    ("is-odd", "3.0.1", &[("is-number", "6.0.0")]),
+];
+
+/// Packages with native addons and the objects node-gyp builds for them
+const NATIVE_MODULES: &[(&str, &str, &[&str])] = &[
+    (
+        "bcrypt",
+        "5.1.1",
+        &["bcrypt_lib/src/blowfish.o", "bcrypt_lib/src/bcrypt.o"],
+    ),
+    (
+        "sharp",
+        "0.33.4",
+        &["sharp/src/common.o", "sharp/src/pipeline.o"],
+    ),
+    (
+        "node-sass",
+        "9.0.0",
+        &["libsass/src/ast.o", "binding/src/binding.o"],
+    ),
+    (
+        "canvas",
+        "2.11.2",
+        &["canvas/src/Canvas.o", "canvas/src/Image.o"],
+    ),
+];
+
+const DEPRECATIONS: &[(&str, &str)] = &[
+    (
+        "inflight@1.0.6",
+        "This module is not supported, and leaks memory.",
+    ),
+    (
+        "rimraf@3.0.2",
+        "Rimraf versions prior to v4 are no longer supported",
+    ),
+    (
+        "glob@7.2.3",
+        "Glob versions prior to v9 are no longer supported",
+    ),
+    (
+        "request@2.88.2",
+        "request has been deprecated, see https://github.com/request/request/issues/3142",
+    ),
+];
+
+pub struct NpmStage {
+    scan: Option<Arc<ScanResult>>,
+}
+
+impl NpmStage {
+    pub fn new(scan: Option<Arc<ScanResult>>) -> Self {
+        Self { scan }
+    }
+
+    /// Scanned project names, made valid as npm package names.
+    fn local_packages(&self) -> Vec<String> {
+        let Some(scan) = &self.scan else {
+            return Vec::new();
+        };
+        scan.project_names
+            .iter()
+            .map(|name| {
+                name.to_lowercase()
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_' {
+                            c
+                        } else {
+                            '-'
+                        }
+                    })
+                    .collect::<String>()
+                    .trim_matches(|c| c == '-' || c == '.' || c == '_')
+                    .to_string()
+            })
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+
+    fn print_tree(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        root: &str,
+        local: &[String],
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        println!("{}@1.0.0", root.bright_white().bold());
+
+        // (name, version, children); local projects come in as file: links
+        let count = rng.gen_range(5..9);
+        let mut entries: Vec<(String, String, &[Package])> = DEPENDENCIES
+            .choose_multiple(rng, count)
+            .map(|(name, version, children)| (name.to_string(), version.to_string(), *children))
+            .collect();
+        for name in local.iter().take(2) {
+            entries.push((name.clone(), format!("1.0.0 -> ./../{}", name), &[]));
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (i, (name, version, children)) in entries.iter().enumerate() {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            let last = i == entries.len() - 1;
+            let branch = match (last, children.is_empty()) {
+                (false, true) => "├──",
+                (false, false) => "├─┬",
+                (true, true) => "└──",
+                (true, false) => "└─┬",
+            };
+            println!("{} {}@{}", branch.dimmed(), name, version.dimmed());
+
+            for (j, (child, child_version)) in children.iter().enumerate() {
+                let stem = if last { " " } else { "│" };
+                let twig = if j == children.len() - 1 {
+                    "└──"
+                } else {
+                    "├──"
+                };
+                println!(
+                    "{} {} {}@{}",
+                    stem.dimmed(),
+                    twig.dimmed(),
+                    child,
+                    child_version.dimmed()
+                );
+            }
+            thread::sleep(Duration::from_millis(60));
+        }
+        Ok(())
+    }
+
+    fn rebuild_native(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let (name, version, objects) = NATIVE_MODULES[rng.gen_range(0..NATIVE_MODULES.len())];
+        let lines = [
+            format!("> {}@{} install", name, version),
+            "> node-pre-gyp install --fallback-to-build".to_string(),
+            String::new(),
+            "gyp info it worked if it ends with ok".to_string(),
+            "gyp info using node-gyp@10.1.0".to_string(),
+            format!(
+                "gyp info using node@20.15.1 | {} | {}",
+                std::env::consts::OS,
+                std::env::consts::ARCH
+            ),
+            "gyp info find Python using Python version 3.12.4 found at \"/usr/bin/python3\""
+                .to_string(),
+            "gyp info spawn make".to_string(),
+            "gyp info spawn args [ 'BUILDTYPE=Release', '-C', 'build' ]".to_string(),
+        ];
+        for line in &lines {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            println!("{}", line.dimmed());
+            thread::sleep(Duration::from_millis(rng.gen_range(40..150)));
+        }
+
+        for object in objects {
+            println!("  CXX(target) Release/obj.target/{}", object);
+            thread::sleep(Duration::from_millis(rng.gen_range(300..900)));
+        }
+        if rng.gen_bool(0.5) {
+            println!(
+                "{}",
+                "warning: 'v8::Local<v8::Value> Nan::MakeCallback(...)' is deprecated [-Wdeprecated-declarations]"
+                    .yellow()
+            );
+        }
+        println!(
+            "  SOLINK_MODULE(target) Release/obj.target/{}.node",
+            name.replace('-', "_")
+        );
+        thread::sleep(Duration::from_millis(rng.gen_range(200..500)));
+        println!("{} {}", "gyp info".dimmed(), "ok".bright_green());
+        println!();
+        Ok(())
+    }
+}
+
+impl InstallationStage for NpmStage {
+    fn name(&self) -> &'static str {
+        "Node.js Dependency Installation"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+
+        let local = self.local_packages();
+        let root = local
+            .choose(&mut rng)
+            .cloned()
+            .unwrap_or_else(|| "my-app".to_string());
+        let siblings: Vec<String> = local.into_iter().filter(|name| *name != root).collect();
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("{}$ npm install", root).bright_white()
+        );
+        println!();
+
+        for message in [
+            "idealTree:#root: sill idealTree buildDeps",
+            "idealTree: sill fetch manifest",
+            "idealTree: timing idealTree Completed",
+        ] {
+            spinner.animate(message, rng.gen_range(600..1500), exit_check)?;
+        }
+
+        let deprecated = rng.gen_range(1..3);
+        for (package, reason) in DEPRECATIONS.choose_multiple(&mut rng, deprecated) {
+            println!(
+                "{} {} {}: {}",
+                "npm".bright_white(),
+                "WARN".black().on_yellow(),
+                format!("deprecated {}", package).bright_magenta(),
+                reason
+            );
+        }
+
+        if rng.gen_bool(0.6) {
+            let warn = || format!("{} {}", "npm".bright_white(), "WARN".black().on_yellow());
+            println!("{} ERESOLVE overriding peer dependency", warn());
+            println!(
+                "{} While resolving: {}",
+                warn(),
+                "react-dom@18.3.1".bright_white()
+            );
+            println!("{} Found: {}", warn(), "react@17.0.2".bright_white());
+            println!("{}   node_modules/react", warn());
+            println!("{}     react@\"^17.0.2\" from the root project", warn());
+            println!("{}", warn());
+            println!("{} Could not resolve dependency:", warn());
+            println!(
+                "{} peer react@\"^18.3.1\" from {}",
+                warn(),
+                "react-dom@18.3.1".bright_white()
+            );
+            thread::sleep(Duration::from_millis(1000));
+        }
+        println!();
+
+        let progress = ProgressBar::new(ProgressStyle::Hash);
+        progress.animate(
+            "reify: timing reifyNode:node_modules",
+            rng.gen_range(2000..4000),
+            exit_check,
+        )?;
+        println!();
+
+        self.rebuild_native(&mut rng, exit_check)?;
+
+        self.print_tree(&mut rng, &root, &siblings, exit_check)?;
+        println!();
+
+        let added = rng.gen_range(400..2000);
+        println!(
+            "added {} packages, and audited {} packages in {}s",
+            added,
+            added + 1,
+            rng.gen_range(8..90)
+        );
+        println!();
+        println!(
+            "{} packages are looking for funding",
+            rng.gen_range(50..300)
+        );
+        println!("  run `npm fund` for details");
+        println!();
+
+        spinner.animate("npm audit", 800, exit_check)?;
+        let counts = [
+            ("low", rng.gen_range(0..8)),
+            ("moderate", rng.gen_range(0..15)),
+            ("high", rng.gen_range(0..10)),
+            ("critical", rng.gen_range(0..4)),
+        ];
+        let total: u32 = counts.iter().map(|(_, n)| n).sum();
+        if total == 0 {
+            println!("found {} vulnerabilities", "0".bright_green().bold());
+        } else {
+            let breakdown: Vec<String> = counts
+                .iter()
+                .filter(|(_, n)| *n > 0)
+                .map(|(severity, n)| {
+                    let label = format!("{} {}", n, severity);
+                    match *severity {
+                        "low" => label.normal().to_string(),
+                        "moderate" => label.yellow().to_string(),
+                        "high" => label.bright_red().to_string(),
+                        _ => label.magenta().bold().to_string(),
+                    }
+                })
+                .collect();
+            println!(
+                "{} vulnerabilities ({})",
+                total.to_string().bright_red().bold(),
+                breakdown.join(", ")
+            );
+            println!();
+            println!("To address issues that do not require attention, run:");
+            println!("  npm audit fix");
+            println!();
+            println!("To address all issues (including breaking changes), run:");
+            println!("  npm audit fix --force");
+            println!();
+            println!("Run `npm audit` for details.");
+        }
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+use super::InstallationStage;
+use crate::host_profile::HostProfile;
+use crate::log_generator::LogGenerator;
+use crate::ui::Spinner;
+use colored::*;
+use rand::Rng;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const SECTOR_SIZE: u64 = 512;
+/// gdisk aligns the first partition on a 1 MiB boundary
+const FIRST_SECTOR: u64 = 2048;
+const BAR_WIDTH: usize = 60;
+
+/// A partition as it will be written to the GPT
+struct Partition {
+    number: u32,
+    start: u64,
+    end: u64,
+    code: &'static str,
+    name: &'static str,
+}
+
+impl Partition {
+    fn sectors(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+pub struct PartitionStage {
+    host: Arc<HostProfile>,
+}
+
+impl PartitionStage {
+    pub fn new(host: Arc<HostProfile>) -> Self {
+        Self { host }
+    }
+
+    fn total_sectors(&self) -> u64 {
+        let capacity_gb = self.host.boot_disk().capacity_gb.max(8);
+        capacity_gb * 1_000_000_000 / SECTOR_SIZE
+    }
+
+    /// Swap sized like the installer guides suggest: RAM, within reason,
+    /// and never more than a quarter of the disk.
+    fn swap_gib(&self) -> u64 {
+        let memory_gib = self.host.total_memory_kb.div_ceil(1024 * 1024);
+        let disk_limit = self.host.boot_disk().capacity_gb.max(8) / 4;
+        memory_gib.clamp(2, 16).min(disk_limit)
+    }
+
+    fn layout(&self) -> Vec<Partition> {
+        let last_usable = self.total_sectors() - 34;
+        let esp_end = FIRST_SECTOR + 512 * 2048 - 1;
+        let swap_sectors = self.swap_gib() * 1024 * 2048;
+        let root_end = last_usable - swap_sectors;
+        vec![
+            Partition {
+                number: 1,
+                start: FIRST_SECTOR,
+                end: esp_end,
+                code: "EF00",
+                name: "EFI system partition",
+            },
+            Partition {
+                number: 2,
+                start: esp_end + 1,
+                end: root_end,
+                code: "8300",
+                name: "Linux filesystem",
+            },
+            Partition {
+                number: 3,
+                start: root_end + 1,
+                end: last_usable,
+                code: "8200",
+                name: "Linux swap",
+            },
+        ]
+    }
+
+    fn human_size(sectors: u64) -> String {
+        let mib = (sectors * SECTOR_SIZE) as f64 / (1024.0 * 1024.0);
+        if mib >= 1024.0 * 1024.0 {
+            format!("{:.1} TiB", mib / (1024.0 * 1024.0))
+        } else if mib >= 1024.0 {
+            format!("{:.1} GiB", mib / 1024.0)
+        } else {
+            format!("{:.1} MiB", mib)
+        }
+    }
+
+    /// Prints a prompt and types the answer one key at a time.
+    fn type_answer(
+        prompt: &str,
+        answer: &str,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+        // Hesitate before the first key, the way people read the prompt
+        thread::sleep(Duration::from_millis(rng.gen_range(300..900)));
+        for c in answer.chars() {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            print!("{}", c.to_string().bright_white());
+            io::stdout().flush()?;
+            thread::sleep(Duration::from_millis(rng.gen_range(60..220)));
+        }
+        thread::sleep(Duration::from_millis(rng.gen_range(100..300)));
+        println!();
+        Ok(())
+    }
+
+    fn print_table(&self, partitions: &[Partition]) {
+        let disk = self.host.boot_disk();
+        let total = self.total_sectors();
+        println!(
+            "Disk {}: {} sectors, {}",
+            disk.device,
+            total,
+            Self::human_size(total)
+        );
+        println!("Model: {}", disk.model);
+        println!("Sector size (logical/physical): 512/4096 bytes");
+        println!("Disk identifier (GUID): {}", Self::guid());
+        println!("Partition table holds up to 128 entries");
+        println!("Main partition table begins at sector 2 and ends at sector 33");
+        println!(
+            "First usable sector is 34, last usable sector is {}",
+            total - 34
+        );
+        println!("Partitions will be aligned on 2048-sector boundaries");
+        let used: u64 = partitions.iter().map(Partition::sectors).sum();
+        println!(
+            "Total free space is {} sectors ({})",
+            total - 34 - 33 - used,
+            Self::human_size(total - 34 - 33 - used)
+        );
+        println!();
+        println!(
+            "{}",
+            "Number  Start (sector)    End (sector)  Size       Code  Name".bright_white()
+        );
+        for partition in partitions {
+            println!(
+                "{:>4}  {:>14}  {:>14}   {:<10} {}  {}",
+                partition.number,
+                partition.start,
+                partition.end,
+                Self::human_size(partition.sectors()),
+                partition.code,
+                partition.name
+            );
+        }
+    }
+
+    fn guid() -> String {
+        let hex = LogGenerator::hex_addr()[2..].to_uppercase();
+        let more = LogGenerator::hex_addr()[2..].to_uppercase();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &more[0..4],
+            &more[4..16]
+        )
+    }
+
+    /// One bar across the terminal, each partition's share drawn in its own
+    /// colour, with the device names underneath.
+    fn print_layout_bar(&self, partitions: &[Partition]) {
+        let disk = self.host.boot_disk();
+        let total: u64 = partitions.iter().map(Partition::sectors).sum();
+        let widths: Vec<usize> = partitions
+            .iter()
+            .map(|p| ((p.sectors() as f64 / total as f64) * BAR_WIDTH as f64).round() as usize)
+            .map(|w| w.max(3))
+            .collect();
+
+        let mut bar = String::new();
+        let mut labels = String::new();
+        for (partition, width) in partitions.iter().zip(&widths) {
+            let segment = "█".repeat(*width);
+            let segment = match partition.code {
+                "EF00" => segment.bright_blue(),
+                "8200" => segment.bright_magenta(),
+                _ => segment.bright_green(),
+            };
+            bar.push_str(&segment.to_string());
+            let device = disk.partition(partition.number);
+            let label = device.trim_start_matches("/dev/");
+            if label.len() < *width {
+                labels.push_str(&format!("{:<width$}", label, width = width));
+            } else {
+                labels.push_str(&format!("{:<width$}", partition.number, width = width));
+            }
+        }
+
+        println!(
+            "{} ({})",
+            disk.device.bright_white(),
+            Self::human_size(total)
+        );
+        println!("{}", bar);
+        println!("{}", labels.dimmed());
+        for partition in partitions {
+            println!(
+                "  {:<16} {:>10}  {}",
+                disk.partition(partition.number),
+                Self::human_size(partition.sectors()),
+                partition.name.dimmed()
+            );
+        }
+    }
+}
+
+impl InstallationStage for PartitionStage {
+    fn name(&self) -> &'static str {
+        "Disk Partitioning"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+        let disk = self.host.boot_disk();
+        let partitions = self.layout();
+        let prompt = "Command (? for help): ";
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("# gdisk {}", disk.device).bright_white()
+        );
+        println!("GPT fdisk (gdisk) version 1.0.10");
+        println!();
+        println!("Partition table scan:");
+        println!("  MBR: protective");
+        println!("  BSD: not present");
+        println!("  APM: not present");
+        println!("  GPT: present");
+        println!();
+        println!("Found valid GPT with protective MBR; using GPT.");
+        println!();
+
+        // Start from a clean table
+        Self::type_answer(prompt, "o", &mut rng, exit_check)?;
+        println!("This option deletes all partitions and creates a new protective MBR.");
+        Self::type_answer("Proceed? (Y/N): ", "Y", &mut rng, exit_check)?;
+        println!();
+
+        let total = self.total_sectors();
+        for partition in &partitions {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            Self::type_answer(prompt, "n", &mut rng, exit_check)?;
+            Self::type_answer(
+                &format!("Partition number (1-128, default {}): ", partition.number),
+                &partition.number.to_string(),
+                &mut rng,
+                exit_check,
+            )?;
+            Self::type_answer(
+                &format!(
+                    "First sector (34-{}, default = {}) or {{+-}}size{{KMGTP}}: ",
+                    total - 34,
+                    partition.start
+                ),
+                "",
+                &mut rng,
+                exit_check,
+            )?;
+            let last_answer = match partition.code {
+                "EF00" => "+512M".to_string(),
+                "8200" => String::new(),
+                _ => format!("-{}G", self.swap_gib()),
+            };
+            Self::type_answer(
+                &format!(
+                    "Last sector ({}-{}, default = {}) or {{+-}}size{{KMGTP}}: ",
+                    partition.start,
+                    total - 34,
+                    total - 34
+                ),
+                &last_answer,
+                &mut rng,
+                exit_check,
+            )?;
+            println!("Current type is 8300 (Linux filesystem)");
+            Self::type_answer(
+                "Hex code or GUID (L to show codes, Enter = 8300): ",
+                if partition.code == "8300" {
+                    ""
+                } else {
+                    partition.code
+                },
+                &mut rng,
+                exit_check,
+            )?;
+            println!("Changed type of partition to '{}'", partition.name);
+            println!();
+        }
+
+        // The ESP was created with the default type first time round often
+        // enough that people double-check it
+        if rng.gen_bool(0.5) {
+            Self::type_answer(prompt, "t", &mut rng, exit_check)?;
+            Self::type_answer(
+                &format!("Partition number (1-{}): ", partitions.len()),
+                "1",
+                &mut rng,
+                exit_check,
+            )?;
+            println!("Current type is EF00 (EFI system partition)");
+            Self::type_answer(
+                "Hex code or GUID (L to show codes, Enter = 8300): ",
+                "ef00",
+                &mut rng,
+                exit_check,
+            )?;
+            println!("Changed type of partition to 'EFI system partition'");
+            println!();
+        }
+
+        Self::type_answer(prompt, "p", &mut rng, exit_check)?;
+        self.print_table(&partitions);
+        println!();
+
+        Self::type_answer(prompt, "w", &mut rng, exit_check)?;
+        println!();
+        println!(
+            "{}",
+            "Final checks complete. About to write GPT data. THIS WILL OVERWRITE EXISTING"
+                .bright_red()
+        );
+        println!("{}", "PARTITIONS!!".bright_red());
+        println!();
+        Self::type_answer("Do you want to proceed? (Y/N): ", "Y", &mut rng, exit_check)?;
+        println!(
+            "OK; writing new GUID partition table (GPT) to {}.",
+            disk.device
+        );
+        thread::sleep(Duration::from_millis(800));
+        println!("The operation has completed successfully.");
+        println!();
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("# partprobe {}", disk.device).bright_white()
+        );
+        spinner.animate("Re-reading partition table...", 1200, exit_check)?;
+        let device = disk.device.trim_start_matches("/dev/");
+        let nodes: Vec<String> = partitions
+            .iter()
+            .map(|p| {
+                disk.partition(p.number)
+                    .trim_start_matches("/dev/")
+                    .to_string()
+            })
+            .collect();
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("kernel:  {}: {}", device, nodes.join(" ")).dimmed()
+        );
+        println!();
+
+        self.print_layout_bar(&partitions);
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
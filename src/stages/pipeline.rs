@@ -0,0 +1,380 @@
+use super::InstallationStage;
+use crate::log_generator::LogGenerator;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use colored::*;
+use crossterm::{
+    cursor, execute,
+    terminal::{self, ClearType},
+};
+use rand::Rng;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Pipeline stages in order; every job in a stage runs at once
+const STAGES: &[&str] = &["lint", "test", "build", "deploy"];
+
+const RUNNERS: &[&str] = &["ubuntu-latest", "macos-14", "windows-2022"];
+
+const TESTS: &[&str] = &[
+    "config::tests::parses_defaults",
+    "scanner::tests::skips_hidden_dirs",
+    "net::tests::reconnect_after_timeout",
+    "storage::tests::concurrent_writes",
+    "cli::tests::rejects_unknown_stage",
+    "cache::tests::evicts_lru_entry",
+    "api::tests::handles_empty_body",
+];
+
+/// The test that fails on the first attempt when a job is flaky
+const FLAKY_TEST: &str = "net::tests::reconnect_after_timeout";
+
+/// (artifact, size in MB)
+const ARTIFACTS: &[(&str, f32)] = &[
+    ("app-x86_64-unknown-linux-gnu.tar.gz", 48.2),
+    ("app-aarch64-apple-darwin.tar.gz", 44.7),
+    ("app-x86_64-pc-windows-msvc.zip", 51.9),
+    ("coverage-report.html", 3.1),
+    ("sbom.spdx.json", 0.4),
+];
+
+/// Ticks a failed flaky test sits on the board before its retry starts
+const RETRY_PAUSE: u32 = 12;
+const TICK_MS: u64 = 100;
+/// Simulated seconds per tick, so jobs read like real CI durations
+const SECONDS_PER_TICK: u32 = 3;
+
+#[derive(PartialEq)]
+enum JobState {
+    Queued,
+    Running,
+    Retrying(u32),
+    Passed,
+}
+
+struct Job {
+    stage: &'static str,
+    name: String,
+    logs: Vec<String>,
+    duration: u32,
+    progress: u32,
+    elapsed: u32,
+    state: JobState,
+    spinner: Spinner,
+    /// Tick at which the flaky test fails on the first attempt
+    flaky_at: Option<u32>,
+    attempt: u32,
+}
+
+impl Job {
+    fn new(
+        stage: &'static str,
+        name: String,
+        logs: Vec<String>,
+        rng: &mut rand::rngs::ThreadRng,
+    ) -> Self {
+        Self {
+            stage,
+            name,
+            logs,
+            duration: rng.gen_range(25..70),
+            progress: 0,
+            elapsed: 0,
+            state: JobState::Queued,
+            spinner: Spinner::new(),
+            flaky_at: None,
+            attempt: 1,
+        }
+    }
+
+    fn tail(&self) -> &str {
+        if self.state == JobState::Queued {
+            return "waiting for runner";
+        }
+        let index = (self.progress as usize * self.logs.len()) / self.duration.max(1) as usize;
+        &self.logs[index.min(self.logs.len() - 1)]
+    }
+
+    /// Ticks the job takes to pass, counting a flaky first attempt and the
+    /// pause before its retry.
+    fn total_ticks(&self) -> u32 {
+        match self.flaky_at {
+            Some(failed_at) => failed_at + RETRY_PAUSE + 1 + self.duration,
+            None => self.duration,
+        }
+    }
+
+    fn tick(&mut self) {
+        if matches!(self.state, JobState::Running | JobState::Retrying(_)) {
+            self.elapsed += 1;
+        }
+        match self.state {
+            JobState::Running => {
+                self.progress += 1;
+                if Some(self.progress) == self.flaky_at && self.attempt == 1 {
+                    self.state = JobState::Retrying(RETRY_PAUSE);
+                } else if self.progress >= self.duration {
+                    self.state = JobState::Passed;
+                }
+            }
+            JobState::Retrying(0) => {
+                self.attempt += 1;
+                self.progress = 0;
+                self.state = JobState::Running;
+            }
+            JobState::Retrying(left) => self.state = JobState::Retrying(left - 1),
+            _ => {}
+        }
+    }
+}
+
+pub struct PipelineStage;
+
+impl PipelineStage {
+    fn cache_line(rng: &mut rand::rngs::ThreadRng, runner: &str) -> String {
+        let key = format!("{}-cargo-{}", runner, &LogGenerator::hex_addr()[2..14]);
+        if rng.gen_bool(0.7) {
+            format!("Cache restored from key: {}", key)
+        } else {
+            format!("Cache not found for input keys: {}", key)
+        }
+    }
+
+    fn jobs(rng: &mut rand::rngs::ThreadRng) -> Vec<Job> {
+        let mut jobs = Vec::new();
+
+        jobs.push(Job::new(
+            "lint",
+            "fmt + clippy".to_string(),
+            vec![
+                Self::cache_line(rng, "ubuntu-latest"),
+                "cargo fmt --all -- --check".to_string(),
+                "cargo clippy --workspace --all-targets -- -D warnings".to_string(),
+                "Checking serde v1.0.204".to_string(),
+                "Checking tokio v1.39.2".to_string(),
+                "Finished `dev` profile [unoptimized + debuginfo]".to_string(),
+            ],
+            rng,
+        ));
+        jobs.push(Job::new(
+            "lint",
+            "cargo deny".to_string(),
+            vec![
+                "cargo deny check advisories licenses".to_string(),
+                "Fetching advisory database from https://github.com/RustSec/advisory-db"
+                    .to_string(),
+                "advisories ok, bans ok, licenses ok, sources ok".to_string(),
+            ],
+            rng,
+        ));
+
+        let total_tests = rng.gen_range(180..400);
+        for runner in RUNNERS {
+            let mut logs = vec![
+                Self::cache_line(rng, runner),
+                "cargo test --workspace --locked".to_string(),
+                format!("running {} tests", total_tests),
+            ];
+            for test in TESTS {
+                logs.push(format!("test {} ... ok", test));
+            }
+            logs.push(format!(
+                "test result: ok. {} passed; 0 failed; 0 ignored",
+                total_tests
+            ));
+            jobs.push(Job::new("test", format!("test ({})", runner), logs, rng));
+        }
+
+        // One matrix entry hits the flaky test on its first attempt
+        if rng.gen_bool(0.6) {
+            let index = jobs.len() - 1 - rng.gen_range(0..RUNNERS.len());
+            let job = &mut jobs[index];
+            let at = job.duration * rng.gen_range(40..80) / 100;
+            job.flaky_at = Some(at);
+        }
+
+        jobs.push(Job::new(
+            "build",
+            "release (3 targets)".to_string(),
+            vec![
+                Self::cache_line(rng, "ubuntu-latest"),
+                "cargo build --release --target x86_64-unknown-linux-gnu".to_string(),
+                "Compiling app v1.4.0 (/home/runner/work/app)".to_string(),
+                "Finished `release` profile [optimized]".to_string(),
+                "Uploading artifact app-x86_64-unknown-linux-gnu.tar.gz".to_string(),
+            ],
+            rng,
+        ));
+        jobs.push(Job::new(
+            "deploy",
+            "production".to_string(),
+            vec![
+                "Waiting for environment protection rules".to_string(),
+                "docker push registry.example.com/app:1.4.0".to_string(),
+                "Rolling update: 1 of 3 pods updated".to_string(),
+                "Rolling update: 2 of 3 pods updated".to_string(),
+                "Rolling update: 3 of 3 pods updated".to_string(),
+                "Health check passed".to_string(),
+            ],
+            rng,
+        ));
+        jobs
+    }
+
+    fn elapsed(ticks: u32) -> String {
+        let seconds = ticks * SECONDS_PER_TICK;
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+
+    /// Redraw every job line plus the overall bar, leaving the cursor below
+    /// the board.
+    fn draw(jobs: &mut [Job], overall: f32, first: bool) -> io::Result<()> {
+        let cols = terminal::size().map(|(c, _)| c as usize).unwrap_or(80);
+        let mut stdout = io::stdout();
+        if !first {
+            execute!(stdout, cursor::MoveUp(jobs.len() as u16 + 1))?;
+        }
+
+        for job in jobs.iter_mut() {
+            let (icon, name) = match job.state {
+                JobState::Queued => ("·".dimmed(), job.name.dimmed()),
+                JobState::Running => (
+                    job.spinner.next().to_string().bright_cyan(),
+                    job.name.bright_white(),
+                ),
+                JobState::Retrying(_) => ("↻".bright_yellow(), job.name.bright_yellow()),
+                JobState::Passed => ("✓".bright_green(), job.name.bright_green()),
+            };
+            let tail = match job.state {
+                JobState::Retrying(_) => format!(
+                    "test {} ... FAILED, retrying (attempt {}/3)",
+                    FLAKY_TEST,
+                    job.attempt + 1
+                ),
+                _ => job.tail().to_string(),
+            };
+            // Two spaces, icon, stage, name and time take 48 columns
+            let room = cols.saturating_sub(49).max(10);
+            let tail: String = tail.chars().take(room).collect();
+            let clock = if job.state == JobState::Queued {
+                "      ".to_string()
+            } else {
+                Self::elapsed(job.elapsed)
+            };
+
+            execute!(stdout, terminal::Clear(ClearType::CurrentLine))?;
+            println!(
+                "  {} {:<7} {:<26} {:>7}  {}",
+                icon,
+                job.stage.dimmed(),
+                name,
+                clock.dimmed(),
+                if matches!(job.state, JobState::Retrying(_)) {
+                    tail.bright_red()
+                } else {
+                    tail.dimmed()
+                }
+            );
+        }
+
+        execute!(stdout, terminal::Clear(ClearType::CurrentLine))?;
+        println!(
+            "  {}",
+            ProgressBar::new(ProgressStyle::Block).render(overall)
+        );
+        stdout.flush()
+    }
+}
+
+impl InstallationStage for PipelineStage {
+    fn name(&self) -> &'static str {
+        "CI/CD Pipeline"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut jobs = Self::jobs(&mut rng);
+        let pipeline = rng.gen_range(10_000..99_999);
+        let commit = &LogGenerator::hex_addr()[2..9];
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!(
+                "Pipeline #{} triggered by push to main ({})",
+                pipeline, commit
+            )
+            .bright_white()
+        );
+        println!();
+
+        let total_ticks: u32 = STAGES
+            .iter()
+            .map(|stage| {
+                jobs.iter()
+                    .filter(|job| job.stage == *stage)
+                    .map(Job::total_ticks)
+                    .max()
+                    .unwrap_or(0)
+            })
+            .sum();
+        let mut ticks = 0;
+
+        Self::draw(&mut jobs, 0.0, true)?;
+        for stage in STAGES {
+            for job in jobs.iter_mut().filter(|job| job.stage == *stage) {
+                job.state = JobState::Running;
+            }
+            while jobs
+                .iter()
+                .any(|job| job.stage == *stage && job.state != JobState::Passed)
+            {
+                if exit_check() {
+                    return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+                }
+                for job in jobs.iter_mut().filter(|job| job.stage == *stage) {
+                    job.tick();
+                }
+                ticks += 1;
+                let overall = (ticks as f32 / total_ticks as f32).min(1.0);
+                Self::draw(&mut jobs, overall, false)?;
+                thread::sleep(Duration::from_millis(TICK_MS));
+            }
+        }
+        Self::draw(&mut jobs, 1.0, false)?;
+        println!();
+
+        let retries = jobs.iter().filter(|job| job.attempt > 1).count();
+        println!("{}", "Artifacts".bright_white().bold());
+        for (artifact, size) in ARTIFACTS {
+            println!(
+                "  {:<40} {:>7.1} MB  sha256:{}",
+                artifact,
+                size,
+                &LogGenerator::hex_addr()[2..14]
+            );
+            thread::sleep(Duration::from_millis(80));
+        }
+        println!();
+        println!(
+            "{} {}",
+            format!("Pipeline #{} passed in {}", pipeline, Self::elapsed(ticks)).bright_green(),
+            if retries > 0 {
+                format!(
+                    "({} flaky {} retried)",
+                    retries,
+                    if retries == 1 { "job" } else { "jobs" }
+                )
+                .yellow()
+            } else {
+                "".normal()
+            }
+        );
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,339 @@
+use super::InstallationStage;
+use crate::config::PythonConfig;
+use crate::log_generator::LogGenerator;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use colored::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// (package, version, wheel size in MB)
+const CONDA_PACKAGES: &[(&str, &str, f32)] = &[
+    ("python", "3.12.4", 31.2),
+    ("numpy", "1.26.4", 6.9),
+    ("pandas", "2.2.2", 12.4),
+    ("scipy", "1.13.1", 26.8),
+    ("pytorch", "2.3.1", 1467.3),
+    ("cudatoolkit", "11.8.0", 715.4),
+    ("mkl", "2023.1.0", 171.5),
+    ("openssl", "3.0.14", 5.2),
+    ("scikit-learn", "1.5.1", 9.7),
+    ("matplotlib-base", "3.8.4", 7.1),
+];
+
+const PIP_PACKAGES: &[(&str, &str, f32)] = &[
+    ("transformers", "4.42.4", 9.3),
+    ("tokenizers", "0.19.1", 3.6),
+    ("accelerate", "0.32.1", 0.3),
+    ("datasets", "2.20.0", 0.5),
+    ("fastapi", "0.111.1", 0.1),
+    ("pydantic-core", "2.20.1", 2.1),
+    ("opencv-python", "4.10.0.84", 62.5),
+    ("tensorflow", "2.16.2", 589.8),
+];
+
+/// Packages that only ship an sdist, so pip has to compile them: (name,
+/// version, extension module, C source)
+const SOURCE_BUILDS: &[(&str, &str, &str, &str)] = &[
+    (
+        "pycocotools",
+        "2.0.8",
+        "pycocotools._mask",
+        "pycocotools/_mask.c",
+    ),
+    ("pyyaml", "5.4.1", "_yaml", "yaml/_yaml.c"),
+    (
+        "psycopg2",
+        "2.9.9",
+        "psycopg2._psycopg",
+        "psycopg/psycopgmodule.c",
+    ),
+    (
+        "llama-cpp-python",
+        "0.2.82",
+        "llama_cpp._C",
+        "vendor/llama.cpp/ggml.c",
+    ),
+];
+
+pub struct PythonStage {
+    config: PythonConfig,
+}
+
+impl PythonStage {
+    pub fn new(config: PythonConfig) -> Self {
+        Self { config }
+    }
+
+    fn solve(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        spinner: &mut Spinner,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        spinner.animate(
+            "Collecting package metadata (current_repodata.json): done",
+            self.config.metadata_time,
+            exit_check,
+        )?;
+
+        if !rng.gen_bool(self.config.failure_rate_frozen_solve) {
+            spinner.animate(
+                "Solving environment: done",
+                rng.gen_range(self.config.solve_time_range.clone()),
+                exit_check,
+            )?;
+            return Ok(());
+        }
+
+        spinner.animate(
+            "Solving environment: failed with initial frozen solve. Retrying with flexible solve.",
+            rng.gen_range(self.config.solve_time_range.clone()),
+            exit_check,
+        )?;
+
+        if rng.gen_bool(self.config.failure_rate_current_repodata) {
+            spinner.animate(
+                "Solving environment: failed with repodata from current_repodata.json, will retry with next repodata source.",
+                rng.gen_range(self.config.solve_time_range.clone()),
+                exit_check,
+            )?;
+            spinner.animate(
+                "Collecting package metadata (repodata.json): done",
+                self.config.metadata_time * 2,
+                exit_check,
+            )?;
+        }
+
+        spinner.animate(
+            "Solving environment: done",
+            rng.gen_range(self.config.solve_time_range.clone()),
+            exit_check,
+        )?;
+        Ok(())
+    }
+
+    fn download(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        name: &str,
+        version: &str,
+        size_mb: f32,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let progress = ProgressBar::new(ProgressStyle::Equals);
+        let speed = rng.gen_range(self.config.download_speed_range.clone());
+        progress.animate(
+            &format!("{:<16} {:<10} {:>8.1} MB", name, version, size_mb),
+            (size_mb * speed as f32) as u64 + 200,
+            exit_check,
+        )
+    }
+
+    fn backtrack(&self, rng: &mut rand::rngs::ThreadRng) {
+        let (package, version, _) = PIP_PACKAGES[rng.gen_range(0..PIP_PACKAGES.len())];
+        println!(
+            "{} pip is looking at multiple versions of {} to determine which version is compatible with other requirements. This could take a while.",
+            "INFO:".bright_cyan(),
+            package
+        );
+        let mut parts = version
+            .split('.')
+            .map(|part| part.parse::<u32>().unwrap_or(0));
+        let major = parts.next().unwrap_or(1);
+        let minor = parts.next().unwrap_or(0);
+        let steps = rng.gen_range(3..7).min(minor);
+        for older in (minor - steps..minor).rev() {
+            println!("Collecting {}", package);
+            println!(
+                "  Downloading {}-{}.{}.0-py3-none-any.whl.metadata ({:.1} kB)",
+                package.replace('-', "_"),
+                major,
+                older,
+                rng.gen_range(1.0..12.0)
+            );
+            thread::sleep(Duration::from_millis(rng.gen_range(150..400)));
+        }
+        println!(
+            "{} This is taking longer than usual. You might need to provide the dependency resolver with stricter constraints to reduce runtime.",
+            "INFO:".bright_cyan()
+        );
+        thread::sleep(Duration::from_millis(1200));
+    }
+
+    fn build_wheel(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        spinner: &mut Spinner,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let (name, version, extension, source) =
+            SOURCE_BUILDS[rng.gen_range(0..SOURCE_BUILDS.len())];
+        let build_temp = format!(
+            "build/temp.{}-{}-cpython-312",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        );
+
+        println!("Building wheels for collected packages: {}", name);
+        println!(
+            "  Building wheel for {} (pyproject.toml) ... ",
+            name.bright_white()
+        );
+        let lines = [
+            "running bdist_wheel".to_string(),
+            "running build".to_string(),
+            "running build_py".to_string(),
+            "running build_ext".to_string(),
+            format!("building '{}' extension", extension),
+            format!("creating {}", build_temp),
+            format!(
+                "gcc -pthread -fno-strict-overflow -Wsign-compare -DNDEBUG -g -O3 -Wall -fPIC -I/opt/conda/include/python3.12 -c {} -o {}/{}",
+                source,
+                build_temp,
+                source.replace(".c", ".o")
+            ),
+        ];
+        for line in &lines {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            println!("    {}", line.dimmed());
+            thread::sleep(Duration::from_millis(rng.gen_range(80..300)));
+        }
+        if rng.gen_bool(self.config.failure_rate_wheel_build) {
+            println!(
+                "    {}",
+                format!(
+                    "{}:12:10: fatal error: Python.h: No such file or directory",
+                    source
+                )
+                .bright_red()
+            );
+            println!("    {}", "compilation terminated.".bright_red());
+            println!(
+                "    {}",
+                "error: command '/usr/bin/gcc' failed with exit code 1".bright_red()
+            );
+            println!(
+                "  {}",
+                format!("ERROR: Failed building wheel for {}", name).bright_red()
+            );
+            println!(
+                "{}",
+                "error: subprocess-exited-with-error".bright_red().bold()
+            );
+            thread::sleep(Duration::from_millis(1000));
+
+            // pip falls back to the legacy install, which somehow works
+            spinner.animate(
+                &format!("  Running setup.py install for {} ... done", name),
+                2500,
+                exit_check,
+            )?;
+            return Ok(());
+        }
+
+        println!(
+            "    {}",
+            format!(
+                "{}: warning: '{}' is deprecated [-Wdeprecated-declarations]",
+                source,
+                ["PyUnicode_AS_UNICODE", "PyEval_InitThreads", "tp_print"]
+                    .choose(rng)
+                    .unwrap()
+            )
+            .yellow()
+        );
+        spinner.animate("    Linking shared object...", 1200, exit_check)?;
+        println!(
+            "  Created wheel for {}: filename={}-{}-cp312-cp312-{}_{}.whl size={} sha256={}",
+            name,
+            name.replace('-', "_"),
+            version,
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            rng.gen_range(80_000..2_000_000),
+            &LogGenerator::hex_addr()[2..]
+        );
+        println!("Successfully built {}", name.bright_green());
+        Ok(())
+    }
+}
+
+impl InstallationStage for PythonStage {
+    fn name(&self) -> &'static str {
+        "Python Environment Setup"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "$ conda env create -f environment.yml".bright_white()
+        );
+        println!("Channels:\n - pytorch\n - nvidia\n - conda-forge\n - defaults");
+        println!("Platform: {}-64", std::env::consts::OS);
+        self.solve(&mut rng, &mut spinner, exit_check)?;
+
+        println!();
+        println!("{}", "Downloading and Extracting Packages:".bright_white());
+        let count = rng.gen_range(4..CONDA_PACKAGES.len());
+        for (name, version, size) in CONDA_PACKAGES.choose_multiple(&mut rng, count) {
+            self.download(&mut rng, name, version, *size, exit_check)?;
+        }
+        spinner.animate("Preparing transaction: done", 800, exit_check)?;
+        spinner.animate("Verifying transaction: done", 1500, exit_check)?;
+        spinner.animate("Executing transaction: done", 2000, exit_check)?;
+
+        println!();
+        println!("{}", "Installing pip dependencies: ".bright_white());
+        let count = rng.gen_range(3..6);
+        let pip: Vec<_> = PIP_PACKAGES.choose_multiple(&mut rng, count).collect();
+        for (name, version, _) in &pip {
+            println!("Collecting {}=={}", name, version);
+            thread::sleep(Duration::from_millis(rng.gen_range(100..400)));
+        }
+
+        if rng.gen_bool(self.config.backtrack_chance) {
+            self.backtrack(&mut rng);
+        }
+
+        for (name, version, size) in &pip {
+            self.download(&mut rng, name, version, *size, exit_check)?;
+        }
+
+        self.build_wheel(&mut rng, &mut spinner, exit_check)?;
+
+        let installed: Vec<String> = pip
+            .iter()
+            .map(|(name, version, _)| format!("{}-{}", name, version))
+            .collect();
+        println!(
+            "Successfully installed {}",
+            installed.join(" ").bright_green()
+        );
+
+        println!();
+        println!("done");
+        println!("#");
+        println!("# To activate this environment, use");
+        println!("#");
+        println!("#     $ conda activate ml-env");
+        println!("#");
+        println!("# To deactivate an active environment, use");
+        println!("#");
+        println!("#     $ conda deactivate");
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
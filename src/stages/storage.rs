@@ -0,0 +1,400 @@
+use super::InstallationStage;
+use crate::config::StorageConfig;
+use crate::host_profile::HostProfile;
+use crate::log_generator::LogGenerator;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use chrono::Local;
+use colored::*;
+use rand::Rng;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Member disk sizes an array might be built from, in GB
+const MEMBER_SIZES_GB: &[u64] = &[2000, 4000, 8000, 12000, 16000];
+
+pub struct StorageStage {
+    config: StorageConfig,
+    host: Arc<HostProfile>,
+}
+
+impl StorageStage {
+    pub fn new(config: StorageConfig, host: Arc<HostProfile>) -> Self {
+        Self { config, host }
+    }
+
+    /// The configured level, or RAID 5 if md wouldn't recognise it.
+    fn level(&self) -> u32 {
+        match self.config.raid_level {
+            0 | 1 | 5 | 6 | 10 => self.config.raid_level,
+            _ => 5,
+        }
+    }
+
+    /// Disks per array, raised to what the level needs and capped at the
+    /// spare names left (and kept even for RAID 10, whose mirrors come in
+    /// pairs).
+    fn disk_count(&self) -> usize {
+        let minimum = match self.level() {
+            1 | 0 => 2,
+            5 => 3,
+            _ => 4,
+        };
+        let count = self.config.disk_count.max(minimum);
+        let available = self.spare_disks(usize::MAX).len();
+        if self.level() == 10 {
+            (count + count % 2).min(available - available % 2)
+        } else {
+            count.min(available)
+        }
+    }
+
+    /// Spare `sdX` names that don't clash with the host's own disks.
+    fn spare_disks(&self, count: usize) -> Vec<String> {
+        let used: Vec<&str> = self
+            .host
+            .disks
+            .iter()
+            .map(|disk| disk.device.trim_start_matches("/dev/"))
+            .collect();
+        ('b'..='z')
+            .map(|letter| format!("sd{}", letter))
+            .filter(|name| !used.contains(&name.as_str()))
+            .take(count)
+            .collect()
+    }
+
+    fn usable_gb(&self, member_gb: u64) -> u64 {
+        let n = self.disk_count() as u64;
+        match self.level() {
+            0 => n * member_gb,
+            1 => member_gb,
+            5 => (n - 1) * member_gb,
+            6 => (n - 2) * member_gb,
+            _ => n / 2 * member_gb,
+        }
+    }
+
+    /// Sizes the way lvs prints them: binary units, lowercase suffix.
+    fn lvm_size(gb: u64) -> String {
+        let gib = gb as f64 * 1e9 / (1u64 << 30) as f64;
+        if gib >= 1024.0 {
+            format!("{:.2}t", gib / 1024.0)
+        } else {
+            format!("{:.2}g", gib)
+        }
+    }
+
+    fn command(text: &str) {
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("# {}", text).bright_white()
+        );
+    }
+
+    fn create_array(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        members: &[String],
+        member_gb: u64,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let level = self.level();
+        let devices: Vec<String> = members.iter().map(|m| format!("/dev/{}", m)).collect();
+        Self::command(&format!(
+            "mdadm --create /dev/md0 --level={} --raid-devices={} {}",
+            level,
+            members.len(),
+            devices.join(" ")
+        ));
+        if level == 5 || level == 6 {
+            println!("mdadm: layout defaults to left-symmetric");
+        }
+        if level != 1 {
+            println!("mdadm: chunk size defaults to 512K");
+        }
+        if level != 0 {
+            println!("mdadm: size set to {}K", member_gb * 1_000_000 / 1024);
+            println!("mdadm: automatically enabling write-intent bitmap on large array");
+        }
+        print!("Continue creating array? ");
+        io::stdout().flush()?;
+        thread::sleep(Duration::from_millis(700));
+        println!("{}", "y".bright_white());
+        println!("mdadm: Defaulting to version 1.2 metadata");
+        println!("mdadm: array /dev/md0 started.");
+        println!();
+        thread::sleep(Duration::from_millis(500));
+
+        Self::command("cat /proc/mdstat");
+        let personalities = match level {
+            0 => "[raid0]",
+            1 => "[raid1]",
+            10 => "[raid10]",
+            _ => "[raid6] [raid5] [raid4]",
+        };
+        println!("Personalities : {}", personalities);
+        let slots: Vec<String> = members
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, member)| format!("{}[{}]", member, i))
+            .collect();
+        println!("md0 : active raid{} {}", level, slots.join(" "));
+
+        let blocks = self.usable_gb(member_gb) * 1_000_000 / 1024;
+        let n = members.len();
+        let healthy = "U".repeat(n);
+        match level {
+            0 => {
+                println!("      {} blocks super 1.2 512k chunks", blocks);
+                println!();
+                println!("unused devices: <none>");
+                return Ok(());
+            }
+            1 => println!(
+                "      {} blocks super 1.2 [{}/{}] [{}]",
+                blocks, n, n, healthy
+            ),
+            10 => println!(
+                "      {} blocks super 1.2 512K chunks 2 near-copies [{}/{}] [{}]",
+                blocks, n, n, healthy
+            ),
+            _ => println!(
+                "      {} blocks super 1.2 level {}, 512k chunk, algorithm 2 [{}/{}] [{}]",
+                blocks, level, n, n, healthy
+            ),
+        }
+
+        // RAID 5 builds by treating the last member as a spare being
+        // recovered onto; everything else resyncs
+        let action = if level == 5 { "recovery" } else { "resync" };
+        let member_kb = member_gb * 1_000_000 / 1024;
+        let mut percent: f64 = rng.gen_range(0.0..4.0);
+        let steps = 60;
+        for step in 0..=steps {
+            if exit_check() {
+                println!();
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            if step == steps {
+                percent = 100.0;
+            }
+            let speed = rng.gen_range(self.config.resync_speed_range.clone());
+            let done_kb = (member_kb as f64 * percent / 100.0) as u64;
+            let finish = (member_kb - done_kb) as f64 / speed as f64 / 60.0;
+            let filled = ((percent / 100.0) * 20.0) as usize;
+            let bar = format!(
+                "[{}>{}]",
+                "=".repeat(filled.min(19)),
+                ".".repeat(19 - filled.min(19))
+            );
+            print!(
+                "\r      {}  {} = {:4.1}% ({}/{}) finish={:.1}min speed={}K/sec   ",
+                bar.bright_green(),
+                action,
+                percent,
+                done_kb,
+                member_kb,
+                finish,
+                speed
+            );
+            io::stdout().flush()?;
+            thread::sleep(Duration::from_millis(self.config.resync_time / steps));
+            percent = (percent + rng.gen_range(0.5..3.0)).min(99.9);
+        }
+        println!();
+        println!(
+            "      bitmap: 0/{} pages [0KB], 65536KB chunk",
+            (member_gb / 128).max(1)
+        );
+        println!();
+        println!("unused devices: <none>");
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            format!("md: md0: {} done.", action).dimmed()
+        );
+        println!();
+        Ok(())
+    }
+
+    fn create_volumes(&self, rng: &mut rand::rngs::ThreadRng, usable_gb: u64) {
+        let root_gb = [50, 100, 200][rng.gen_range(0..3)];
+        let var_gb = [20, 50, 100][rng.gen_range(0..3)];
+
+        Self::command("pvcreate /dev/md0");
+        println!("  Physical volume \"/dev/md0\" successfully created.");
+        thread::sleep(Duration::from_millis(300));
+        Self::command("vgcreate vg_data /dev/md0");
+        println!("  Volume group \"vg_data\" successfully created");
+        thread::sleep(Duration::from_millis(300));
+        Self::command(&format!("lvcreate -L {}G -n lv_root vg_data", root_gb));
+        println!("  Logical volume \"lv_root\" created.");
+        thread::sleep(Duration::from_millis(300));
+        Self::command(&format!("lvcreate -L {}G -n lv_var vg_data", var_gb));
+        println!("  Logical volume \"lv_var\" created.");
+        thread::sleep(Duration::from_millis(300));
+        Self::command("lvcreate -l 100%FREE -n lv_home vg_data");
+        println!("  Logical volume \"lv_home\" created.");
+        thread::sleep(Duration::from_millis(300));
+
+        let home_gb = usable_gb.saturating_sub(root_gb + var_gb);
+        Self::command("lvs");
+        println!(
+            "{}",
+            "  LV      VG      Attr       LSize   Pool Origin Data%  Meta%  Move Log Cpy%Sync Convert"
+                .bright_white()
+        );
+        for (name, gb) in [
+            ("lv_home", home_gb),
+            ("lv_root", root_gb),
+            ("lv_var", var_gb),
+        ] {
+            println!("  {} vg_data -wi-a----- {:>7}", name, Self::lvm_size(gb));
+        }
+        println!();
+    }
+
+    fn create_pool(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        members: &[String],
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        // (vdev name, disks per vdev); striped pools have no vdev line
+        let (vdev, width) = match self.level() {
+            0 => ("", 1),
+            1 => ("mirror", members.len()),
+            5 => ("raidz1", members.len()),
+            6 => ("raidz2", members.len()),
+            _ => ("mirror", 2),
+        };
+        let groups: Vec<&[String]> = members.chunks(width).collect();
+        let spec: Vec<String> = groups
+            .iter()
+            .map(|group| {
+                if vdev.is_empty() {
+                    group.join(" ")
+                } else {
+                    format!("{} {}", vdev, group.join(" "))
+                }
+            })
+            .collect();
+        Self::command(&format!(
+            "zpool create -o ashift=12 -O compression=lz4 tank {}",
+            spec.join(" ")
+        ));
+        thread::sleep(Duration::from_millis(800));
+
+        let healthy = |indent: &str, name: &str| {
+            println!(
+                "\t{}{:<width$}ONLINE       0     0     0",
+                indent,
+                name,
+                width = 12 - indent.len()
+            );
+        };
+        let print_status = |scan: Option<String>| {
+            println!("  pool: tank");
+            println!(" state: {}", "ONLINE".bright_green());
+            if let Some(scan) = &scan {
+                println!("  scan: {}", scan);
+            }
+            println!("config:");
+            println!();
+            println!(
+                "\t{}",
+                "NAME        STATE     READ WRITE CKSUM".bright_white()
+            );
+            healthy("", "tank");
+            for (i, group) in groups.iter().enumerate() {
+                if vdev.is_empty() {
+                    for disk in group.iter() {
+                        healthy("  ", disk);
+                    }
+                } else {
+                    healthy("  ", &format!("{}-{}", vdev, i));
+                    for disk in group.iter() {
+                        healthy("    ", disk);
+                    }
+                }
+            }
+            println!();
+            println!("errors: No known data errors");
+        };
+
+        Self::command("zpool status tank");
+        print_status(None);
+        println!();
+
+        Self::command("zpool scrub tank");
+        let progress = ProgressBar::new(ProgressStyle::Gradient);
+        progress.animate(
+            "scrub in progress on tank",
+            self.config.scrub_time,
+            exit_check,
+        )?;
+
+        let seconds = rng.gen_range(2..(self.config.scrub_time / 1000).max(3) + 2);
+        Self::command("zpool status tank");
+        print_status(Some(format!(
+            "scrub repaired 0B in 00:00:{:02} with 0 errors on {}",
+            seconds,
+            Local::now().format("%a %b %e %H:%M:%S %Y")
+        )));
+        println!();
+        Ok(())
+    }
+}
+
+impl InstallationStage for StorageStage {
+    fn name(&self) -> &'static str {
+        "Storage Array Assembly"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+        let count = self.disk_count();
+        let member_gb = MEMBER_SIZES_GB[rng.gen_range(0..MEMBER_SIZES_GB.len())];
+
+        // The md array and the ZFS pool each get their own set of disks
+        let disks = self.spare_disks(count * 2);
+        let (array_disks, pool_disks) = disks.split_at(count.min(disks.len()));
+        let build_pool = pool_disks.len() == count && rng.gen_bool(self.config.zfs_chance);
+        let pool_disks = if build_pool { pool_disks } else { &[] };
+
+        spinner.animate("Scanning for block devices...", 1200, exit_check)?;
+        for disk in array_disks.iter().chain(pool_disks) {
+            println!(
+                "{} {}",
+                LogGenerator::timestamp().dimmed(),
+                format!(
+                    "sd: [{}] {} 512-byte logical blocks: ({} GB)",
+                    disk,
+                    member_gb * 1_000_000_000 / 512,
+                    member_gb
+                )
+                .dimmed()
+            );
+            thread::sleep(Duration::from_millis(80));
+        }
+        println!();
+
+        self.create_array(&mut rng, array_disks, member_gb, exit_check)?;
+        self.create_volumes(&mut rng, self.usable_gb(member_gb));
+
+        if build_pool {
+            self.create_pool(&mut rng, pool_disks, exit_check)?;
+        }
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,356 @@
+use super::InstallationStage;
+use crate::config::TrainingConfig;
+use crate::log_generator::LogGenerator;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use colored::*;
+use rand::Rng;
+use std::f64::consts::PI;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// Optimizer steps each logged line stands for
+const STEPS_PER_LOG: usize = 250;
+const PEAK_LR: f64 = 3e-4;
+const MIN_LR: f64 = 3e-5;
+/// Fraction of training spent warming the learning rate up
+const WARMUP_FRACTION: f64 = 0.05;
+
+/// An exponential approach from `start` towards `end`, with a fresh shape for
+/// every run so no two trainings look alike.
+struct Curve {
+    start: f64,
+    end: f64,
+    rate: f64,
+}
+
+impl Curve {
+    /// Value at `t`, the fraction of training done.
+    fn at(&self, t: f64) -> f64 {
+        self.end + (self.start - self.end) * (-self.rate * t).exp()
+    }
+}
+
+pub struct TrainingStage {
+    config: TrainingConfig,
+}
+
+impl TrainingStage {
+    pub fn new(config: TrainingConfig) -> Self {
+        Self { config }
+    }
+
+    fn log(message: impl std::fmt::Display) {
+        println!("{} {}", LogGenerator::timestamp().dimmed(), message);
+    }
+
+    fn node_name(node: usize) -> String {
+        format!("gpu-node-{:02}", node)
+    }
+
+    fn world_size(&self) -> usize {
+        self.config.node_count * self.config.gpus_per_node
+    }
+
+    /// Linear warmup, then cosine decay down to `MIN_LR`.
+    fn learning_rate(step: usize, total: usize) -> f64 {
+        let warmup = (total as f64 * WARMUP_FRACTION).max(1.0);
+        let step = step as f64;
+        if step < warmup {
+            PEAK_LR * step / warmup
+        } else {
+            let t = (step - warmup) / (total as f64 - warmup);
+            MIN_LR + 0.5 * (PEAK_LR - MIN_LR) * (1.0 + (PI * t).cos())
+        }
+    }
+
+    fn nccl_init(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let world = self.world_size();
+        Self::log(
+            format!(
+                "$ torchrun --nnodes={} --nproc_per_node={} --rdzv_backend=c10d --rdzv_endpoint={}:29400 train.py --config configs/7b.yaml",
+                self.config.node_count,
+                self.config.gpus_per_node,
+                Self::node_name(0)
+            )
+            .bright_white(),
+        );
+        let mut spinner = Spinner::new();
+        spinner.animate(
+            &format!(
+                "Waiting for {} nodes to join rendezvous...",
+                self.config.node_count
+            ),
+            1500,
+            exit_check,
+        )?;
+        Self::log(format!(
+            "torch.distributed.elastic: rendezvous complete, world_size={} ({} nodes x {} GPUs)",
+            world, self.config.node_count, self.config.gpus_per_node
+        ));
+
+        let pid = rng.gen_range(2000..40000);
+        Self::log(format!(
+            "{}:{}:{} [0] NCCL INFO Bootstrap : Using ens5:10.42.0.10<0>",
+            Self::node_name(0),
+            pid,
+            pid
+        ));
+        Self::log(format!(
+            "{}:{}:{} [0] NCCL INFO NET/IB : Using [0]mlx5_0:1/IB [1]mlx5_1:1/IB ; OOB ens5:10.42.0.10<0>",
+            Self::node_name(0),
+            pid,
+            pid
+        ));
+        Self::log(format!(
+            "{}:{}:{} [0] NCCL INFO cudaDriverVersion 12040, NCCL version 2.21.5+cuda12.4",
+            Self::node_name(0),
+            pid,
+            pid
+        ));
+
+        let channels = 16;
+        let ring: Vec<String> = (0..world.min(12)).map(|r| r.to_string()).collect();
+        for channel in 0..4 {
+            Self::log(format!(
+                "{}:{}:{} [0] NCCL INFO Channel {:02}/{:02} :    {}{}",
+                Self::node_name(0),
+                pid,
+                pid,
+                channel,
+                channels,
+                ring.join(" "),
+                if world > ring.len() { " ..." } else { "" }
+            ));
+            thread::sleep(Duration::from_millis(rng.gen_range(40..120)));
+        }
+
+        for node in 0..self.config.node_count {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            let first_rank = node * self.config.gpus_per_node;
+            Self::log(format!(
+                "{} NCCL INFO Connected all rings, ranks {}-{} ({} GB/s busbw)",
+                Self::node_name(node),
+                first_rank,
+                first_rank + self.config.gpus_per_node - 1,
+                rng.gen_range(160..190)
+            ));
+            thread::sleep(Duration::from_millis(rng.gen_range(150..400)));
+        }
+        Self::log(format!(
+            "{}:{}:{} [0] NCCL INFO comm 0x{:x} rank 0 nranks {} cudaDev 0 busId 18000 - Init COMPLETE",
+            Self::node_name(0),
+            pid,
+            pid,
+            rng.gen_range(0x5500_0000_0000u64..0x56ff_ffff_ffffu64),
+            world
+        ));
+        println!();
+        Ok(())
+    }
+
+    fn save_checkpoint(
+        &self,
+        epoch: usize,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        Self::log(format!(
+            "Saving checkpoint to checkpoints/epoch_{:03}/ ({} shards)",
+            epoch,
+            self.world_size()
+        ));
+        let progress = ProgressBar::new(ProgressStyle::Block);
+        progress.animate("  Writing shards", rng.gen_range(800..1800), exit_check)?;
+        Self::log(
+            format!(
+                "Checkpoint saved: {:.1} GB model + optimizer state in {:.1}s",
+                rng.gen_range(78.0..84.0),
+                rng.gen_range(9.0..40.0)
+            )
+            .green(),
+        );
+        Ok(())
+    }
+
+    fn straggler(&self, rng: &mut rand::rngs::ThreadRng) {
+        let node = rng.gen_range(0..self.config.node_count);
+        let rank = node * self.config.gpus_per_node + rng.gen_range(0..self.config.gpus_per_node);
+        Self::log(
+            format!(
+                "WARNING: straggler detected: rank {} on {} step time {:.2}s ({:.1}x median), all-reduce waiting",
+                rank,
+                Self::node_name(node),
+                rng.gen_range(2.0..6.0),
+                rng.gen_range(2.5..4.5)
+            )
+            .yellow(),
+        );
+        thread::sleep(Duration::from_millis(900));
+        Self::log(
+            format!(
+                "{} nvidia-smi: GPU {} clocks throttled (HW Slowdown: thermal, {}C)",
+                Self::node_name(node),
+                rank % self.config.gpus_per_node,
+                rng.gen_range(86..93)
+            )
+            .yellow(),
+        );
+        thread::sleep(Duration::from_millis(700));
+        Self::log(format!(
+            "rank {} back within 1.1x of median step time",
+            rank
+        ));
+    }
+
+    /// A fp16 overflow: the step is skipped, the loss scale halved and
+    /// training carries on from the weights it had.
+    fn nan_recovery(&self, step: usize, loss_scale: &mut u32, rng: &mut rand::rngs::ThreadRng) {
+        Self::log(
+            format!("step {:>6} | loss nan | grad_norm inf | lr --", step)
+                .bright_red()
+                .bold(),
+        );
+        Self::log(
+            format!(
+                "Detected non-finite loss on {} ranks at step {}; skipping optimizer step",
+                rng.gen_range(1..self.world_size()),
+                step
+            )
+            .bright_red(),
+        );
+        thread::sleep(Duration::from_millis(800));
+        let halved = *loss_scale / 2;
+        Self::log(
+            format!(
+                "GradScaler: reducing loss scale {} -> {}",
+                loss_scale, halved
+            )
+            .yellow(),
+        );
+        *loss_scale = halved;
+        thread::sleep(Duration::from_millis(500));
+        Self::log("Resuming training with previous weights".yellow());
+    }
+}
+
+impl InstallationStage for TrainingStage {
+    fn name(&self) -> &'static str {
+        "Distributed Training Job"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_magenta().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        self.nccl_init(&mut rng, exit_check)?;
+
+        let epochs = self.config.epochs;
+        let lines = self.config.log_lines_per_epoch;
+        let total_steps = epochs * lines * STEPS_PER_LOG;
+
+        // Loss starts near ln(vocab size), as an untrained model guessing
+        // uniformly would
+        let loss = Curve {
+            start: rng.gen_range(10.3..10.9),
+            end: rng.gen_range(1.8..2.6),
+            rate: rng.gen_range(4.0..7.0),
+        };
+        let accuracy = Curve {
+            start: rng.gen_range(0.01..0.03),
+            end: rng.gen_range(0.52..0.64),
+            rate: rng.gen_range(3.0..5.0),
+        };
+
+        let straggler_epoch = rng
+            .gen_bool(self.config.straggler_chance)
+            .then(|| rng.gen_range(1..=epochs));
+        // Never in the first epoch, while the loss scale is still settling
+        let nan_line = (epochs > 1 && rng.gen_bool(self.config.nan_chance))
+            .then(|| rng.gen_range(lines..epochs * lines));
+        let mut loss_scale: u32 = 65536;
+        let tokens_per_second = rng.gen_range(2.8..3.6) * self.world_size() as f64 / 32.0;
+
+        Self::log(format!(
+            "Training for {} epochs, {} steps, global batch 1024 x 4096 tokens",
+            epochs, total_steps
+        ));
+        Self::log(format!(
+            "LR schedule: linear warmup {} steps to {:.1e}, cosine decay to {:.1e}",
+            (total_steps as f64 * WARMUP_FRACTION) as usize,
+            PEAK_LR,
+            MIN_LR
+        ));
+        println!();
+
+        let mut train_loss = loss.start;
+        for epoch in 1..=epochs {
+            for line in 0..lines {
+                if exit_check() {
+                    return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+                }
+                let index = (epoch - 1) * lines + line;
+                let step = (index + 1) * STEPS_PER_LOG;
+                let t = step as f64 / total_steps as f64;
+
+                // A skipped step gets no loss line of its own
+                if nan_line == Some(index) {
+                    self.nan_recovery(step, &mut loss_scale, &mut rng);
+                } else {
+                    train_loss = loss.at(t) * rng.gen_range(0.97..1.03);
+                    let grad_norm = rng.gen_range(0.3..1.2) + 2.0 * (-8.0 * t).exp();
+                    Self::log(format!(
+                        "epoch {}/{} step {:>6}/{} | loss {} | lr {:.2e} | grad_norm {:.3} | {:.2}M tok/s",
+                        epoch,
+                        epochs,
+                        step,
+                        total_steps,
+                        format!("{:.4}", train_loss).bright_white(),
+                        Self::learning_rate(step, total_steps),
+                        grad_norm,
+                        tokens_per_second * rng.gen_range(0.95..1.05)
+                    ));
+                }
+                thread::sleep(Duration::from_millis(
+                    rng.gen_range(self.config.step_delay_range.clone()),
+                ));
+
+                if straggler_epoch == Some(epoch) && line == lines / 2 {
+                    self.straggler(&mut rng);
+                }
+            }
+
+            let t = epoch as f64 / epochs as f64;
+            // Validation trails training a little more the longer it runs
+            let val_loss = train_loss + 0.05 + 0.15 * t + rng.gen_range(-0.02..0.02);
+            let val_acc = (accuracy.at(t) + rng.gen_range(-0.01..0.01)) * 100.0;
+            Self::log(
+                format!(
+                    "epoch {} done | train_loss {:.4} | val_loss {:.4} | val_ppl {:.2} | val_acc {:.2}%",
+                    epoch,
+                    train_loss,
+                    val_loss,
+                    val_loss.exp(),
+                    val_acc
+                )
+                .bright_cyan(),
+            );
+
+            if epoch % self.config.checkpoint_interval == 0 || epoch == epochs {
+                self.save_checkpoint(epoch, &mut rng, exit_check)?;
+            }
+            println!();
+        }
+
+        Self::log("Training complete. Destroying process group.".green());
+        thread::sleep(Duration::from_millis(500));
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use super::InstallationStage;
+use crate::config::WindowsConfig;
+use crate::host_profile::HostProfile;
+use crate::log_generator::LogGenerator;
+use crate::ui::{ProgressBar, ProgressStyle, Spinner};
+use colored::*;
+use rand::Rng;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Width of the text-mode setup screen
+const SCREEN_WIDTH: usize = 78;
+
+/// Characters Microsoft used in product keys (no vowels, no 0/1/5)
+const KEY_CHARS: &[u8] = b"BCDFGHJKMPQRTVWXY2346789";
+
+const SETUP_FILES: &[&str] = &[
+    "ntoskrnl.exe",
+    "hal.dll",
+    "ntdll.dll",
+    "kernel32.dll",
+    "user32.dll",
+    "gdi32.dll",
+    "win32k.sys",
+    "ntfs.sys",
+    "atapi.sys",
+    "explorer.exe",
+    "shell32.dll",
+    "comctl32.dll",
+    "msvcrt.dll",
+    "rpcrt4.dll",
+    "winlogon.exe",
+    "lsass.exe",
+    "services.exe",
+    "svchost.exe",
+    "mshtml.dll",
+    "clippy.acs",
+];
+
+/// (stop code name, number, driver blamed)
+const STOP_CODES: &[(&str, u32, &str)] = &[
+    ("IRQL_NOT_LESS_OR_EQUAL", 0x0A, "ntoskrnl.exe"),
+    ("PAGE_FAULT_IN_NONPAGED_AREA", 0x50, "win32k.sys"),
+    ("KERNEL_DATA_INPAGE_ERROR", 0x7A, "atapi.sys"),
+    ("INACCESSIBLE_BOOT_DEVICE", 0x7B, "ntfs.sys"),
+    ("DRIVER_IRQL_NOT_LESS_OR_EQUAL", 0xD1, "tcpip.sys"),
+    ("UNMOUNTABLE_BOOT_VOLUME", 0xED, "ntfs.sys"),
+];
+
+pub struct WindowsStage {
+    config: WindowsConfig,
+    host: Arc<HostProfile>,
+}
+
+impl WindowsStage {
+    pub fn new(config: WindowsConfig, host: Arc<HostProfile>) -> Self {
+        Self { config, host }
+    }
+
+    /// Print a full-width line of the blue text-mode screen.
+    fn blue_line(text: &str) {
+        println!(
+            "{}",
+            format!(" {:<width$}", text, width = SCREEN_WIDTH - 1)
+                .bright_white()
+                .on_blue()
+        );
+    }
+
+    fn product_key(rng: &mut impl Rng) -> String {
+        (0..5)
+            .map(|_| {
+                (0..5)
+                    .map(|_| KEY_CHARS[rng.gen_range(0..KEY_CHARS.len())] as char)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    fn enter_product_key(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        Self::blue_line("");
+        Self::blue_line("Your Product Key");
+        Self::blue_line("");
+        Self::blue_line("Type the 25-character Product Key that came with your copy of Windows.");
+        Self::blue_line("");
+
+        let key = Self::product_key(rng);
+        print!("{}", "  Product Key: ".bright_white().on_blue());
+        io::stdout().flush()?;
+        for c in key.chars() {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            print!("{}", c.to_string().bright_yellow().on_blue());
+            io::stdout().flush()?;
+            thread::sleep(Duration::from_millis(
+                rng.gen_range(self.config.key_entry_delay_range.clone()),
+            ));
+        }
+        println!();
+        thread::sleep(Duration::from_millis(300));
+        Self::blue_line("");
+        Ok(())
+    }
+
+    fn copy_files(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        Self::blue_line(
+            "Please wait while Setup copies files to the Windows installation folders.",
+        );
+        Self::blue_line("This might take several minutes to complete.");
+        Self::blue_line("");
+
+        let progress = ProgressBar::new(ProgressStyle::Block);
+        let count = rng.gen_range(self.config.file_count_range.clone());
+        for i in 0..=count {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            let file = SETUP_FILES[rng.gen_range(0..SETUP_FILES.len())];
+            print!(
+                "\r  Setup is copying files... {}  {:<24}",
+                progress.render(i as f32 / count as f32),
+                format!("Copying: {}", file).dimmed()
+            );
+            io::stdout().flush()?;
+            thread::sleep(Duration::from_millis(
+                rng.gen_range(self.config.file_copy_delay_range.clone()),
+            ));
+        }
+        println!();
+        Ok(())
+    }
+
+    /// Classic stop screen followed by a memory dump. Returns once the machine
+    /// has "restarted".
+    fn blue_screen(
+        &self,
+        rng: &mut rand::rngs::ThreadRng,
+        exit_check: &dyn Fn() -> bool,
+    ) -> io::Result<()> {
+        let (name, code, driver) = STOP_CODES[rng.gen_range(0..STOP_CODES.len())];
+        let param = || format!("0x{:08X}", rand::thread_rng().gen::<u32>());
+
+        println!();
+        Self::blue_line("");
+        Self::blue_line(
+            "A problem has been detected and Windows has been shut down to prevent damage",
+        );
+        Self::blue_line("to your computer.");
+        Self::blue_line("");
+        Self::blue_line(name);
+        Self::blue_line("");
+        Self::blue_line("If this is the first time you've seen this Stop error screen,");
+        Self::blue_line("restart your computer. If this screen appears again, follow");
+        Self::blue_line("these steps:");
+        Self::blue_line("");
+        Self::blue_line("Check to make sure any new hardware or software is properly installed.");
+        Self::blue_line("");
+        Self::blue_line("Technical information:");
+        Self::blue_line("");
+        Self::blue_line(&format!(
+            "*** STOP: 0x{:08X} ({}, {}, {}, {})",
+            code,
+            param(),
+            param(),
+            param(),
+            param()
+        ));
+        Self::blue_line("");
+        Self::blue_line(&format!(
+            "***  {} - Address {} base at {}, DateStamp {}",
+            driver,
+            &LogGenerator::hex_addr()[10..],
+            &LogGenerator::hex_addr()[10..],
+            &LogGenerator::hex_addr()[10..],
+        ));
+        Self::blue_line("");
+        Self::blue_line("Beginning dump of physical memory");
+        thread::sleep(Duration::from_millis(800));
+
+        let mut dumped = 0;
+        while dumped < 100 {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            dumped = (dumped + rng.gen_range(1..6)).min(100);
+            print!(
+                "\r{}",
+                format!(
+                    " {:<width$}",
+                    format!("Dumping physical memory to disk: {:3}", dumped),
+                    width = SCREEN_WIDTH - 1
+                )
+                .bright_white()
+                .on_blue()
+            );
+            io::stdout().flush()?;
+            thread::sleep(Duration::from_millis(self.config.dump_step_delay));
+        }
+        println!();
+        Self::blue_line("Physical memory dump complete.");
+        Self::blue_line("Contact your system administrator or technical support group for further");
+        Self::blue_line("assistance.");
+        thread::sleep(Duration::from_millis(1000));
+
+        self.restart_countdown("Restarting", exit_check)?;
+
+        println!();
+        println!(
+            "{}",
+            "Windows could not finish configuring the system. Setup will now resume."
+                .bright_white()
+        );
+        thread::sleep(Duration::from_millis(800));
+        Ok(())
+    }
+
+    fn restart_countdown(&self, label: &str, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        for remaining in (1..=self.config.restart_countdown).rev() {
+            if exit_check() {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+            }
+            print!(
+                "\r  {} in {} second{}... ",
+                label,
+                remaining,
+                if remaining == 1 { "" } else { "s" }
+            );
+            io::stdout().flush()?;
+            thread::sleep(Duration::from_millis(self.config.restart_tick));
+        }
+        println!();
+        Ok(())
+    }
+}
+
+impl InstallationStage for WindowsStage {
+    fn name(&self) -> &'static str {
+        "Windows Setup"
+    }
+
+    fn run(&self, exit_check: &dyn Fn() -> bool) -> io::Result<()> {
+        println!("\n{}", format!("> {}", self.name()).bright_yellow().bold());
+        println!();
+
+        let mut rng = rand::thread_rng();
+        let mut spinner = Spinner::new();
+
+        Self::blue_line("Windows Setup");
+        Self::blue_line(&"═".repeat(13));
+        Self::blue_line("");
+        spinner.animate(
+            "Setup is inspecting your computer's hardware configuration...",
+            self.config.inspect_time,
+            exit_check,
+        )?;
+
+        // Partition list uses the real boot disk so it matches the other stages
+        let disk = self.host.boot_disk();
+        let disk_mb = disk.capacity_gb.max(1) * 1000;
+        let free_mb = disk_mb * rng.gen_range(5..40) / 100;
+        println!();
+        Self::blue_line(&format!(
+            "{} MB Disk 0 at Id 0 on bus 0 on {} [MBR]",
+            disk_mb,
+            disk.driver()
+        ));
+        Self::blue_line(&format!(
+            "    C:  Partition1 [NTFS]        {:>8} MB ( {:>8} MB free)",
+            disk_mb, free_mb
+        ));
+        Self::blue_line("");
+        thread::sleep(Duration::from_millis(600));
+
+        self.enter_product_key(&mut rng, exit_check)?;
+        self.copy_files(&mut rng, exit_check)?;
+
+        println!();
+        spinner.animate(
+            "Setup is updating registry settings...",
+            self.config.registry_time,
+            exit_check,
+        )?;
+
+        if rng.gen_bool(self.config.bsod_chance) {
+            self.blue_screen(&mut rng, exit_check)?;
+            spinner.animate(
+                "Setup is updating registry settings...",
+                self.config.registry_time,
+                exit_check,
+            )?;
+        }
+
+        spinner.animate(
+            "Setup is saving your settings...",
+            self.config.registry_time / 2,
+            exit_check,
+        )?;
+        spinner.animate(
+            "Setup is removing any temporary files used...",
+            self.config.registry_time / 2,
+            exit_check,
+        )?;
+
+        println!();
+        self.restart_countdown(
+            "This portion of Setup has completed. Restarting",
+            exit_check,
+        )?;
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "Starting Windows...".bright_white()
+        );
+        thread::sleep(Duration::from_millis(500));
+
+        Ok(())
+    }
+}
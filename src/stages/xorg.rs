@@ -1,15 +1,23 @@
 use super::InstallationStage;
+use crate::host_profile::HostProfile;
 use crate::log_generator::LogGenerator;
 use crate::ui::Spinner;
 use colored::*;
 use rand::Rng;
 use std::io::{self, Write};
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-pub struct XorgStage;
+pub struct XorgStage {
+    host: Arc<HostProfile>,
+}
 
 impl XorgStage {
+    pub fn new(host: Arc<HostProfile>) -> Self {
+        Self { host }
+    }
+
     fn load_xorg_modules(
         &self,
         rng: &mut rand::rngs::ThreadRng,
@@ -113,6 +121,7 @@ impl XorgStage {
         );
         thread::sleep(Duration::from_millis(rng.gen_range(300..600)));
 
+        // (name, driver, PCI slot, device ID, modes, integrated)
         let gpu_configs = [
             (
                 "Intel UHD Graphics 630",
@@ -120,6 +129,15 @@ impl XorgStage {
                 "00:02.0",
                 "8086:9bc8",
                 vec!["1920x1080", "2560x1440", "1680x1050"],
+                true,
+            ),
+            (
+                "AMD Radeon Graphics",
+                "amdgpu",
+                "05:00.0",
+                "1002:1638",
+                vec!["1920x1080", "2560x1440", "1920x1200"],
+                true,
             ),
             (
                 "NVIDIA GeForce RTX 3080",
@@ -127,6 +145,7 @@ impl XorgStage {
                 "01:00.0",
                 "10de:2206",
                 vec!["3840x2160", "2560x1440", "1920x1080"],
+                false,
             ),
             (
                 "AMD Radeon RX 6800 XT",
@@ -134,6 +153,7 @@ impl XorgStage {
                 "01:00.0",
                 "1002:73bf",
                 vec!["3840x2160", "2560x1440", "1920x1080"],
+                false,
             ),
             (
                 "VirtualBox Graphics Adapter",
@@ -141,11 +161,25 @@ impl XorgStage {
                 "00:02.0",
                 "80ee:beef",
                 vec!["1920x1080", "1280x720"],
+                false,
             ),
         ];
 
-        let (gpu_name, driver, pci_slot, device_id, modes) =
-            &gpu_configs[rng.gen_range(0..gpu_configs.len())];
+        // Usually the integrated GPU that goes with the host's CPU, otherwise
+        // anything that could plausibly be plugged in
+        let cpu = self.host.cpu_brand.to_lowercase();
+        let integrated = if cpu.contains("intel") {
+            Some(0)
+        } else if cpu.contains("amd") {
+            Some(1)
+        } else {
+            None
+        };
+        let index = match integrated {
+            Some(index) if rng.gen_bool(0.7) => index,
+            _ => rng.gen_range(2..gpu_configs.len()),
+        };
+        let (gpu_name, driver, pci_slot, device_id, modes, integrated) = &gpu_configs[index];
 
         println!(
             "{}   └─ Scanning PCI device {}",
@@ -205,15 +239,19 @@ impl XorgStage {
         );
         thread::sleep(Duration::from_millis(300));
 
-        let vram = if driver == &"nvidia" || driver == &"amdgpu" {
-            rng.gen_range(8..=16)
+        // Integrated GPUs carve their VRAM out of system memory
+        let vram_mb = if *integrated {
+            (self.host.total_memory_kb / 1024 / 2).clamp(512, 8192)
         } else if driver == &"vboxvideo" {
             128
         } else {
-            rng.gen_range(4..=8)
+            rng.gen_range(8..=16) * 1024
+        };
+        let vram_display = if vram_mb >= 1024 {
+            format!("{} GB", vram_mb / 1024)
+        } else {
+            format!("{} MB", vram_mb)
         };
-        let vram_unit = if vram >= 1024 { "GB" } else { "MB" };
-        let vram_display = if vram >= 1024 { vram / 1024 } else { vram };
 
         println!();
         println!(
@@ -225,17 +263,22 @@ impl XorgStage {
         println!(
             "{}   ├─ Total VRAM: {} {}",
             LogGenerator::timestamp().dimmed(),
-            format!("{} {}", vram_display, vram_unit).bright_white(),
-            format!("(dedicated)").dimmed()
+            vram_display.bright_white(),
+            if *integrated {
+                "(shared)"
+            } else {
+                "(dedicated)"
+            }
+            .dimmed()
         );
         thread::sleep(Duration::from_millis(200));
         println!(
             "{}   └─ Memory type: {}",
             LogGenerator::timestamp().dimmed(),
-            if driver == &"nvidia" || driver == &"amdgpu" {
-                "GDDR6"
-            } else {
+            if *integrated || driver == &"vboxvideo" {
                 "Shared"
+            } else {
+                "GDDR6"
             }
             .bright_white()
         );
@@ -249,12 +292,12 @@ impl XorgStage {
         );
         thread::sleep(Duration::from_millis(500));
 
-        let outputs = if driver == &"nvidia" || driver == &"amdgpu" {
-            vec!["DisplayPort-0", "HDMI-0", "DVI-D-0"]
+        let outputs = if *integrated {
+            vec!["eDP-1", "HDMI-1", "DP-1"]
         } else if driver == &"vboxvideo" {
             vec!["Virtual-1"]
         } else {
-            vec!["eDP-1", "HDMI-1", "DP-1"]
+            vec!["DisplayPort-0", "HDMI-0", "DVI-D-0"]
         };
 
         for (i, output) in outputs.iter().enumerate() {
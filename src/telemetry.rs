@@ -0,0 +1,199 @@
+use std::path::Path;
+use sysinfo::{CpuRefreshKind, Disks, MemoryRefreshKind, ProcessRefreshKind, RefreshKind, System};
+
+/// Process names worth commenting on, lowercased without `.exe`, mapped to
+/// the name shown to the user.
+const KNOWN_APPS: &[(&str, &str)] = &[
+    // Browsers
+    ("firefox", "Firefox"),
+    ("chrome", "Chrome"),
+    ("google chrome", "Chrome"),
+    ("chromium", "Chromium"),
+    ("brave", "Brave"),
+    ("brave browser", "Brave"),
+    ("safari", "Safari"),
+    ("msedge", "Edge"),
+    ("arc", "Arc"),
+    // Editors
+    ("code", "VS Code"),
+    ("cursor", "Cursor"),
+    ("zed", "Zed"),
+    ("nvim", "Neovim"),
+    ("vim", "Vim"),
+    ("emacs", "Emacs"),
+    ("sublime_text", "Sublime Text"),
+    ("idea", "IntelliJ IDEA"),
+    ("pycharm", "PyCharm"),
+    ("xcode", "Xcode"),
+    // Chat
+    ("slack", "Slack"),
+    ("discord", "Discord"),
+    ("teams", "Teams"),
+    ("ms-teams", "Teams"),
+    ("zoom", "Zoom"),
+    ("zoom.us", "Zoom"),
+    ("signal", "Signal"),
+    ("telegram", "Telegram"),
+    // Music
+    ("spotify", "Spotify"),
+    ("music", "Music"),
+    ("rhythmbox", "Rhythmbox"),
+    ("vlc", "VLC"),
+    // Other terminals
+    ("iterm2", "iTerm2"),
+    ("alacritty", "Alacritty"),
+    ("kitty", "kitty"),
+    ("wezterm-gui", "WezTerm"),
+    ("ghostty", "Ghostty"),
+    ("gnome-terminal-server", "GNOME Terminal"),
+    ("konsole", "Konsole"),
+    ("windowsterminal", "Windows Terminal"),
+];
+
+/// Live hardware readings, sampled once per installation cycle so that
+/// messages quote numbers that actually move while the installer runs.
+#[derive(Debug, Clone)]
+pub struct Telemetry {
+    pub cpu_model: Option<String>,
+    pub cpu_cores: usize,
+    pub ram_total_gb: f64,
+    pub ram_used_gb: f64,
+    /// Percentage of the root (or largest) disk in use.
+    pub disk_used_percent: Option<u8>,
+    /// e.g. "87% (Discharging)"; `None` when there is no battery to report.
+    pub battery: Option<String>,
+    pub uptime_secs: u64,
+    /// One-minute load average; `None` where the platform doesn't provide one.
+    pub load_average: Option<f64>,
+    /// Recognised applications running alongside the installer, by name only.
+    pub processes: Vec<String>,
+    pub process_count: usize,
+}
+
+impl Telemetry {
+    /// Take a fresh reading. The process list is only walked when
+    /// `watch_processes` is set.
+    pub fn sample(watch_processes: bool) -> Self {
+        let mut refresh = RefreshKind::new()
+            .with_cpu(CpuRefreshKind::new().with_frequency())
+            .with_memory(MemoryRefreshKind::new().with_ram());
+        if watch_processes {
+            refresh = refresh.with_processes(ProcessRefreshKind::new());
+        }
+        let sys = System::new_with_specifics(refresh);
+
+        let cpu_model = sys
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string())
+            .filter(|brand| !brand.is_empty());
+
+        let gib = 1024.0 * 1024.0 * 1024.0;
+        let load = System::load_average().one;
+
+        Self {
+            cpu_model,
+            cpu_cores: sys.cpus().len(),
+            ram_total_gb: sys.total_memory() as f64 / gib,
+            ram_used_gb: sys.used_memory() as f64 / gib,
+            disk_used_percent: root_disk_usage(),
+            battery: battery_state(),
+            uptime_secs: System::uptime(),
+            load_average: (load > 0.0).then_some(load),
+            processes: recognised_apps(&sys),
+            process_count: sys.processes().len(),
+        }
+    }
+}
+
+fn recognised_apps(sys: &System) -> Vec<String> {
+    let mut apps: Vec<String> = sys
+        .processes()
+        .values()
+        .filter_map(|process| {
+            let name = process.name().to_string_lossy().to_lowercase();
+            let name = name.strip_suffix(".exe").unwrap_or(&name);
+            KNOWN_APPS
+                .iter()
+                .find(|(process_name, _)| *process_name == name)
+                .map(|(_, app)| app.to_string())
+        })
+        .collect();
+    apps.sort();
+    apps.dedup();
+    apps
+}
+
+/// Format an uptime as the two largest units, e.g. "3 days, 4 hours".
+pub fn format_uptime(secs: u64) -> String {
+    let days = secs / 86_400;
+    let hours = (secs % 86_400) / 3_600;
+    let minutes = (secs % 3_600) / 60;
+
+    let unit = |n: u64, name: &str| format!("{} {}{}", n, name, if n == 1 { "" } else { "s" });
+
+    if days > 0 {
+        format!("{}, {}", unit(days, "day"), unit(hours, "hour"))
+    } else if hours > 0 {
+        format!("{}, {}", unit(hours, "hour"), unit(minutes, "minute"))
+    } else {
+        unit(minutes, "minute")
+    }
+}
+
+fn root_disk_usage() -> Option<u8> {
+    let disks = Disks::new_with_refreshed_list();
+    let disk = disks
+        .iter()
+        .find(|d| d.mount_point() == Path::new("/"))
+        .or_else(|| disks.iter().max_by_key(|d| d.total_space()))?;
+
+    let total = disk.total_space();
+    if total == 0 {
+        return None;
+    }
+    let used = total.saturating_sub(disk.available_space());
+    Some((used * 100 / total) as u8)
+}
+
+/// sysinfo has no battery support, so read the kernel's power-supply class
+/// directly where it exists.
+#[cfg(target_os = "linux")]
+fn battery_state() -> Option<String> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let is_battery = std::fs::read_to_string(path.join("type"))
+            .map(|t| t.trim() == "Battery")
+            .unwrap_or(false);
+        if !is_battery {
+            continue;
+        }
+
+        let capacity = std::fs::read_to_string(path.join("capacity")).ok()?;
+        let status = std::fs::read_to_string(path.join("status")).unwrap_or_default();
+        return Some(match status.trim() {
+            "" => format!("{}%", capacity.trim()),
+            status => format!("{}% ({})", capacity.trim(), status),
+        });
+    }
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn battery_state() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uptime_uses_two_largest_units() {
+        assert_eq!(format_uptime(59), "0 minutes");
+        assert_eq!(format_uptime(60), "1 minute");
+        assert_eq!(format_uptime(3 * 3_600 + 12 * 60), "3 hours, 12 minutes");
+        assert_eq!(format_uptime(86_400 + 3_600), "1 day, 1 hour");
+    }
+}
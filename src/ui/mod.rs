@@ -1,4 +1,6 @@
 pub mod progress;
+mod prompt;
 mod spinner;
 pub use progress::{ProgressBar, ProgressStyle};
+pub use prompt::{prompt_key, prompt_retry};
 pub use spinner::Spinner;
@@ -1,4 +1,5 @@
 use colored::*;
+use rand::Rng;
 use std::io::{self, Write};
 use std::thread;
 use std::time::Duration;
@@ -8,10 +9,21 @@ pub enum ProgressStyle {
     Hash,
     Equals,
     Block,
-    #[allow(dead_code)]
     Gradient,
+    /// Cassette loading: the filled part flickers in border-stripe colours
+    Tape,
 }
 
+/// Border colours a tape loader flashes through
+const TAPE_COLORS: &[Color] = &[
+    Color::Red,
+    Color::Cyan,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::BrightWhite,
+];
+
 pub struct ProgressBar {
     width: usize,
     style: ProgressStyle,
@@ -37,16 +49,38 @@ impl ProgressBar {
                     ('▒', '░')
                 }
             }
+            ProgressStyle::Tape => ('▌', ' '),
+        };
+
+        let fill = match self.style {
+            ProgressStyle::Tape => Self::tape_stripes(fill_char, filled),
+            _ => fill_char
+                .to_string()
+                .repeat(filled)
+                .bright_green()
+                .to_string(),
         };
 
         format!(
             "[{}{}] {:3.0}%",
-            fill_char.to_string().repeat(filled).bright_green(),
+            fill,
             empty_char.to_string().repeat(empty).dimmed(),
             progress * 100.0
         )
     }
 
+    /// Each cell in a random border colour, redrawn every frame so the bar
+    /// flickers like a loader reading bits off the tape.
+    fn tape_stripes(fill_char: char, filled: usize) -> String {
+        let mut rng = rand::thread_rng();
+        (0..filled)
+            .map(|_| {
+                let color = TAPE_COLORS[rng.gen_range(0..TAPE_COLORS.len())];
+                fill_char.to_string().color(color).to_string()
+            })
+            .collect()
+    }
+
     pub fn animate(
         &self,
         message: &str,
@@ -0,0 +1,63 @@
+use crate::log_generator::LogGenerator;
+use colored::*;
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Print `question` and block until one of `keys` is pressed (ignoring
+/// case), echoing it. Returns the index of the key in `keys`.
+///
+/// Ctrl+C comes back as `ErrorKind::Interrupted`: the stages' exit check
+/// reads keys too, so a prompt that swallowed it would trap the user.
+pub fn prompt_key(question: &str, keys: &[char]) -> io::Result<usize> {
+    print!("{}", question);
+    io::stdout().flush()?;
+
+    loop {
+        let Event::Key(key_event) = event::read()? else {
+            continue;
+        };
+        let KeyCode::Char(c) = key_event.code else {
+            continue;
+        };
+        if c == 'c' && key_event.modifiers.contains(KeyModifiers::CONTROL) {
+            println!();
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "User interrupt"));
+        }
+        if let Some(index) = keys.iter().position(|key| key.eq_ignore_ascii_case(&c)) {
+            println!("{}", keys[index]);
+            return Ok(index);
+        }
+    }
+}
+
+/// The "Try again or abort?" prompt after a failed build. Returns whether
+/// to retry, having printed `retry_message` or the abort notice.
+pub fn prompt_retry(retry_message: &str) -> io::Result<bool> {
+    println!();
+    let retry = prompt_key(
+        &"Try again or abort? [1-2]: "
+            .bright_yellow()
+            .bold()
+            .to_string(),
+        &['1', '2'],
+    )? == 0;
+
+    if retry {
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            retry_message.bright_cyan()
+        );
+        thread::sleep(Duration::from_millis(1000));
+    } else {
+        println!(
+            "{} {}",
+            LogGenerator::timestamp().dimmed(),
+            "Aborting...".bright_red()
+        );
+        thread::sleep(Duration::from_millis(500));
+    }
+    Ok(retry)
+}